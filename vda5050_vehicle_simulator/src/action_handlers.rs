@@ -0,0 +1,178 @@
+//! Pluggable VDA5050 action handlers: one [`ActionHandler`] per `action_type`, dispatched by
+//! [`VehicleSimulator`]'s registry instead of a single hard-coded `match`. Covers the baseline
+//! VDA5050 action vocabulary (`initPosition`, `startCharging`/`stopCharging`, `pick`/`drop`,
+//! `pause`/`startPause`, `resume`/`stopPause`, `cancelOrder`, `factsheetRequest`) so any of those
+//! `action_type`s sent by master control actually mutates vehicle state instead of just being
+//! logged as unknown. Users can register additional handlers, or override one of these by
+//! `action_type`, via [`VehicleSimulator::register_action_handler`].
+
+use crate::protocol::vda_2_0_0::vda5050_2_0_0_action::{Action, ActionParameterValue};
+use crate::protocol::vda_2_0_0::vda5050_2_0_0_state::Load;
+use crate::vehicle_simulator::VehicleSimulator;
+
+/// Executes one VDA5050 `action_type`'s side effects when that action transitions to `Running`.
+pub trait ActionHandler: Send {
+    /// The `action_type` string (e.g. `"pick"`) this handler executes, used as its registry key.
+    fn action_type(&self) -> &str;
+
+    /// Apply this action's effect to `sim`, returning an optional `result_description` to record
+    /// on the action's `ActionState`.
+    fn execute(&self, sim: &mut VehicleSimulator, action: &Action) -> Option<String>;
+}
+
+/// Every handler [`VehicleSimulator::new`] registers out of the box.
+pub(crate) fn default_handlers() -> Vec<Box<dyn ActionHandler>> {
+    vec![
+        Box::new(InitPositionHandler),
+        Box::new(StartChargingHandler),
+        Box::new(StopChargingHandler),
+        Box::new(PauseHandler { action_type: "pause" }),
+        Box::new(PauseHandler { action_type: "startPause" }),
+        Box::new(ResumeHandler { action_type: "resume" }),
+        Box::new(ResumeHandler { action_type: "stopPause" }),
+        Box::new(CancelOrderHandler),
+        Box::new(PickHandler),
+        Box::new(DropHandler),
+        Box::new(FactsheetRequestHandler),
+    ]
+}
+
+struct InitPositionHandler;
+
+impl ActionHandler for InitPositionHandler {
+    fn action_type(&self) -> &str {
+        "initPosition"
+    }
+
+    fn execute(&self, sim: &mut VehicleSimulator, action: &Action) -> Option<String> {
+        sim.handle_init_position_action(action);
+        None
+    }
+}
+
+struct StartChargingHandler;
+
+impl ActionHandler for StartChargingHandler {
+    fn action_type(&self) -> &str {
+        "startCharging"
+    }
+
+    fn execute(&self, sim: &mut VehicleSimulator, _action: &Action) -> Option<String> {
+        sim.state.battery_state.charging = true;
+        Some("Charging started".to_string())
+    }
+}
+
+struct StopChargingHandler;
+
+impl ActionHandler for StopChargingHandler {
+    fn action_type(&self) -> &str {
+        "stopCharging"
+    }
+
+    fn execute(&self, sim: &mut VehicleSimulator, _action: &Action) -> Option<String> {
+        sim.state.battery_state.charging = false;
+        Some(format!("Charging stopped at {:.1}%", sim.state.battery_state.battery_charge))
+    }
+}
+
+/// Backs both `"pause"` and `"startPause"`, which the simulator treats as synonyms.
+struct PauseHandler {
+    action_type: &'static str,
+}
+
+impl ActionHandler for PauseHandler {
+    fn action_type(&self) -> &str {
+        self.action_type
+    }
+
+    fn execute(&self, sim: &mut VehicleSimulator, action: &Action) -> Option<String> {
+        sim.handle_pause_action(&action.action_id);
+        None
+    }
+}
+
+/// Backs both `"resume"` and `"stopPause"`, which the simulator treats as synonyms.
+struct ResumeHandler {
+    action_type: &'static str,
+}
+
+impl ActionHandler for ResumeHandler {
+    fn action_type(&self) -> &str {
+        self.action_type
+    }
+
+    fn execute(&self, sim: &mut VehicleSimulator, action: &Action) -> Option<String> {
+        sim.handle_resume_action(&action.action_id);
+        None
+    }
+}
+
+struct CancelOrderHandler;
+
+impl ActionHandler for CancelOrderHandler {
+    fn action_type(&self) -> &str {
+        "cancelOrder"
+    }
+
+    fn execute(&self, sim: &mut VehicleSimulator, action: &Action) -> Option<String> {
+        sim.handle_cancel_order_action(&action.action_id);
+        None
+    }
+}
+
+struct PickHandler;
+
+impl ActionHandler for PickHandler {
+    fn action_type(&self) -> &str {
+        "pick"
+    }
+
+    fn execute(&self, sim: &mut VehicleSimulator, action: &Action) -> Option<String> {
+        sim.state.loads.push(Load {
+            load_id: string_param(action, "loadId"),
+            load_type: string_param(action, "loadType"),
+            load_position: string_param(action, "loadPosition"),
+        });
+        Some("Load picked up".to_string())
+    }
+}
+
+struct DropHandler;
+
+impl ActionHandler for DropHandler {
+    fn action_type(&self) -> &str {
+        "drop"
+    }
+
+    fn execute(&self, sim: &mut VehicleSimulator, action: &Action) -> Option<String> {
+        match string_param(action, "loadId") {
+            Some(load_id) => sim.state.loads.retain(|load| load.load_id.as_deref() != Some(load_id.as_str())),
+            None => sim.state.loads.clear(),
+        }
+        Some("Load dropped off".to_string())
+    }
+}
+
+/// The simulator doesn't model a `Factsheet` message type, so this only acknowledges the
+/// request via `result_description` rather than actually publishing one.
+struct FactsheetRequestHandler;
+
+impl ActionHandler for FactsheetRequestHandler {
+    fn action_type(&self) -> &str {
+        "factsheetRequest"
+    }
+
+    fn execute(&self, _sim: &mut VehicleSimulator, _action: &Action) -> Option<String> {
+        Some("Factsheet publishing is not modeled by this simulator".to_string())
+    }
+}
+
+/// Value of `key` among `action.action_parameters`, stringified regardless of its wire type.
+fn string_param(action: &Action, key: &str) -> Option<String> {
+    action.action_parameters.as_ref()?.iter().find(|param| param.key == key).map(|param| match &param.value {
+        ActionParameterValue::Str(s) => s.clone(),
+        ActionParameterValue::Int(i) => i.to_string(),
+        ActionParameterValue::Float(f) => f.to_string(),
+    })
+}
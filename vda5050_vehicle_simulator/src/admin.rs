@@ -0,0 +1,137 @@
+//! Embedded HTTP admin server for a running [`VehicleSimulator`](crate::vehicle_simulator::VehicleSimulator): a `/metrics` endpoint in
+//! Prometheus text exposition format and a `/state` endpoint in JSON, so an operator can scrape
+//! the internal health of a fleet of simulated AGVs the same way a metrics module gets bolted
+//! onto any other RPC/storage-focused service, instead of having to infer it from the MQTT
+//! traffic.
+
+use axum::extract::State as AxumState;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Serialize;
+
+use crate::fleet_simulator::SharedFleet;
+use crate::protocol::vda_2_0_0::vda5050_2_0_0_state::ActionStatus;
+
+/// Every [`ActionStatus`] variant, in the fixed order [`MetricsSnapshot::action_states_by_status`]
+/// reports counts in.
+const ACTION_STATUSES: [ActionStatus; 6] = [
+    ActionStatus::Waiting,
+    ActionStatus::Initializing,
+    ActionStatus::Running,
+    ActionStatus::Paused,
+    ActionStatus::Finished,
+    ActionStatus::Failed,
+];
+
+/// Point-in-time view of the fields worth exposing to an operator, computed once per request so
+/// `/metrics` and `/state` always render from the same consistent snapshot of the simulator.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSnapshot {
+    pub serial_number: String,
+    pub battery_charge: f32,
+    pub driving: bool,
+    pub order_id: String,
+    pub order_update_id: u32,
+    pub pending_node_states: usize,
+    pub pending_edge_states: usize,
+    pub action_states_by_status: Vec<(ActionStatus, usize)>,
+    pub connection_header_id: u32,
+    pub state_header_id: u32,
+    pub visualization_header_id: u32,
+    pub x: f32,
+    pub y: f32,
+    pub theta: f32,
+}
+
+/// Render `snapshot` as Prometheus text exposition format: one `agv_*` gauge per series, each
+/// labeled with `serial_number` so a single scrape target can be reused across a fleet of
+/// simulated AGVs.
+pub fn render_prometheus(snapshot: &MetricsSnapshot) -> String {
+    let labels = format!("serial_number=\"{}\"", snapshot.serial_number);
+    let mut out = String::new();
+
+    let mut gauge = |name: &str, help: &str, value: f64| {
+        out.push_str(&format!(
+            "# HELP {name} {help}\n# TYPE {name} gauge\n{name}{{{labels}}} {value}\n"
+        ));
+    };
+
+    gauge("agv_battery_charge", "Battery charge percentage, 0-100.", snapshot.battery_charge as f64);
+    gauge("agv_driving", "1 if the AGV is currently driving/rotating, 0 otherwise.", snapshot.driving as u8 as f64);
+    gauge("agv_order_update_id", "order_update_id of the current or previous order.", snapshot.order_update_id as f64);
+    gauge("agv_pending_node_states", "Number of node_states not yet reached.", snapshot.pending_node_states as f64);
+    gauge("agv_pending_edge_states", "Number of edge_states not yet traversed.", snapshot.pending_edge_states as f64);
+    gauge("agv_connection_header_id", "header_id last published on the connection topic.", snapshot.connection_header_id as f64);
+    gauge("agv_state_header_id", "header_id last published on the state topic.", snapshot.state_header_id as f64);
+    gauge("agv_visualization_header_id", "header_id last published on the visualization topic.", snapshot.visualization_header_id as f64);
+    gauge("agv_position_x", "Current AGV x position.", snapshot.x as f64);
+    gauge("agv_position_y", "Current AGV y position.", snapshot.y as f64);
+    gauge("agv_position_theta", "Current AGV theta orientation.", snapshot.theta as f64);
+
+    out.push_str("# HELP agv_action_states Number of action_states currently in each ActionStatus.\n# TYPE agv_action_states gauge\n");
+    for (status, count) in &snapshot.action_states_by_status {
+        out.push_str(&format!("agv_action_states{{{labels},action_status=\"{status:?}\"}} {count}\n"));
+    }
+
+    out
+}
+
+/// Count `action_states` by [`ActionStatus`], one entry per [`ACTION_STATUSES`] variant (even if
+/// its count is zero), so `/metrics` always emits a stable set of series.
+pub(crate) fn count_action_states_by_status(
+    action_states: &[crate::protocol::vda_2_0_0::vda5050_2_0_0_state::ActionState],
+) -> Vec<(ActionStatus, usize)> {
+    ACTION_STATUSES
+        .iter()
+        .map(|&status| {
+            let count = action_states.iter().filter(|action_state| action_state.action_status == status).count();
+            (status, count)
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+struct AdminState {
+    fleet: SharedFleet,
+    vehicle_index: usize,
+}
+
+impl AdminState {
+    async fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.fleet.lock().await.vehicles()[self.vehicle_index].metrics_snapshot()
+    }
+}
+
+async fn metrics_handler(AxumState(state): AxumState<AdminState>) -> impl IntoResponse {
+    render_prometheus(&state.metrics_snapshot().await)
+}
+
+async fn state_handler(AxumState(state): AxumState<AdminState>) -> impl IntoResponse {
+    Json(state.metrics_snapshot().await)
+}
+
+/// Serve `/metrics` and `/state` for the vehicle at `vehicle_index` within `fleet` on
+/// `bind_address` until the process exits. Meant to be spawned as its own task alongside a
+/// vehicle's MQTT dispatch; a bind failure is logged and the task simply exits, since a simulator
+/// with no admin surface still runs fine, it just can't be scraped.
+pub async fn serve_admin(bind_address: String, fleet: SharedFleet, vehicle_index: usize) {
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .route("/state", get(state_handler))
+        .with_state(AdminState { fleet, vehicle_index });
+
+    let listener = match tokio::net::TcpListener::bind(&bind_address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("Error binding admin server to {}: {:?}", bind_address, e);
+            return;
+        }
+    };
+
+    println!("Admin server listening on {}", bind_address);
+    if let Err(e) = axum::serve(listener, app).await {
+        println!("Admin server error: {:?}", e);
+    }
+}
@@ -10,6 +10,21 @@ pub struct MqttBrokerConfig {
     pub host: String,
     pub port: String,
     pub vda_interface: String,
+    /// MQTT protocol version to connect with: `"3.1.1"` or `"5"`. Selects which
+    /// `paho_mqtt::ConnectOptions` code path `mqtt_handler::build_connect_opts` builds. Both
+    /// versions support the Last-Will-and-Testament registered there; `"5"` additionally lets a
+    /// future client attach v5-only properties (message expiry, user properties) to it.
+    pub protocol_version: String,
+    /// Base delay, in milliseconds, before the first reconnect retry after a dropped connection,
+    /// doubling on each subsequent attempt up to `reconnect_max_backoff_secs`. See
+    /// `mqtt_transport::BackoffConfig`.
+    pub reconnect_initial_backoff_ms: u64,
+    /// Upper bound, in seconds, the exponential reconnect backoff is clamped to.
+    pub reconnect_max_backoff_secs: u64,
+    /// Number of reconnect attempts to make before giving up and leaving the vehicle
+    /// disconnected. Set to a very large value to retry indefinitely, since there's no literal
+    /// "unlimited".
+    pub reconnect_max_attempts: u32,
 }
 
 #[derive(Deserialize, Clone)]
@@ -23,16 +38,59 @@ pub struct VehicleConfig {
 #[derive(Deserialize, Clone)]
 pub struct Settings {
     pub action_time: f32,
+    /// Cruising speed the AGV ramps toward, in distance units per simulation tick.
     pub speed: f32,
+    /// How fast `VehicleSimulator`'s velocity can ramp up toward `speed` each tick.
+    pub max_acceleration: f32,
+    /// How fast `VehicleSimulator`'s velocity can ramp down toward 0 each tick when braking for
+    /// the next node.
+    pub max_deceleration: f32,
     pub robot_count: u32,
     pub state_frequency: u64,
     pub visualization_frequency: u64,
     pub map_id: String,
 }
 
+#[derive(Deserialize, Clone)]
+pub struct AdminConfig {
+    /// Whether to start the embedded HTTP admin server alongside `VehicleSimulator` at all.
+    pub enabled: bool,
+    /// Address (`host:port`) the admin server's `/metrics` and `/state` endpoints bind to.
+    pub bind_address: String,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PersistenceConfig {
+    /// Whether to rehydrate `State` from, and periodically/on-shutdown save it to, `path`.
+    pub enabled: bool,
+    /// File path `State` is serialized to and loaded from.
+    pub path: String,
+    /// How often, in seconds, the vehicle's state is saved to `path` while running, in addition
+    /// to always saving once on clean shutdown.
+    pub save_interval_secs: u64,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct OutboundQueueConfig {
+    /// How many buffered visualization frames to replay, in order, once the connection is back.
+    /// State is always kept as just the single latest message regardless of this setting, since
+    /// VDA5050 state is last-value-wins and replaying a backlog of stale state messages would be
+    /// pointless. See `outbound_queue::OutboundQueue`.
+    pub visualization_buffer_depth: usize,
+    /// What happens when a new visualization frame arrives and the buffer is already at
+    /// `visualization_buffer_depth`: `"drop_oldest"` discards the oldest buffered frame to make
+    /// room, replaying the most recent window of the outage; `"keep_latest_only"` discards
+    /// everything buffered so far and keeps just the new frame, replaying only the final
+    /// pre-reconnect sample. Defaults to `"drop_oldest"` for anything else.
+    pub drop_policy: String,
+}
+
 #[derive(Deserialize, Clone)]
 pub struct Config {
     pub mqtt_broker: MqttBrokerConfig,
     pub vehicle: VehicleConfig,
-    pub settings: Settings
+    pub settings: Settings,
+    pub admin: AdminConfig,
+    pub persistence: PersistenceConfig,
+    pub outbound_queue: OutboundQueueConfig,
 }
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::vehicle_simulator::VehicleSimulator;
+
+/// How many ticks a granted reservation remains valid for before it must be renewed. Chosen to
+/// comfortably span the time it takes a vehicle to clear a node/edge at typical simulator speeds;
+/// a vehicle that is still using a node/edge simply re-reserves it on its next tick.
+const RESERVATION_HOLD_TICKS: u64 = 5;
+
+/// A reservation granted to one vehicle over a node or edge for a bounded window of ticks.
+#[derive(Clone, Debug)]
+struct Reservation {
+    holder: String,
+    until_tick: u64,
+}
+
+/// Outcome of attempting to acquire a reservation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReservationOutcome {
+    /// The reservation was granted (either it was free, already expired, or already held by the
+    /// same vehicle).
+    Granted,
+    /// The reservation is currently held by another vehicle, identified by serial number.
+    HeldBy(String),
+}
+
+/// Result of one fleet-aware `update_state` tick for a single vehicle.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrafficStatus {
+    /// The vehicle moved (or had nothing to reserve, e.g. no active order).
+    Proceeding,
+    /// The vehicle is holding position because the node/edge it needs next is reserved by the
+    /// named vehicle.
+    WaitingOn(String),
+}
+
+/// Time-windowed reservation table shared by every vehicle in a [`FleetSimulator`], keyed
+/// separately by node id and edge id, used to guarantee that no two AGVs ever occupy the same
+/// node or traverse the same edge at once.
+#[derive(Default)]
+pub struct ReservationTable {
+    nodes: HashMap<String, Reservation>,
+    edges: HashMap<String, Reservation>,
+}
+
+impl ReservationTable {
+    fn try_reserve(
+        table: &mut HashMap<String, Reservation>,
+        key: &str,
+        holder: &str,
+        current_tick: u64,
+    ) -> ReservationOutcome {
+        if let Some(reservation) = table.get(key) {
+            if reservation.holder != holder && reservation.until_tick > current_tick {
+                return ReservationOutcome::HeldBy(reservation.holder.clone());
+            }
+        }
+
+        table.insert(
+            key.to_string(),
+            Reservation {
+                holder: holder.to_string(),
+                until_tick: current_tick + RESERVATION_HOLD_TICKS,
+            },
+        );
+        ReservationOutcome::Granted
+    }
+
+    /// Reserve `node_id` for `holder`, or report who currently holds it.
+    pub fn reserve_node(&mut self, node_id: &str, holder: &str, current_tick: u64) -> ReservationOutcome {
+        Self::try_reserve(&mut self.nodes, node_id, holder, current_tick)
+    }
+
+    /// Reserve `edge_id` for `holder`, or report who currently holds it.
+    pub fn reserve_edge(&mut self, edge_id: &str, holder: &str, current_tick: u64) -> ReservationOutcome {
+        Self::try_reserve(&mut self.edges, edge_id, holder, current_tick)
+    }
+
+    /// Drop every node/edge reservation currently held by `holder`, making them immediately
+    /// available to other vehicles. Used to break a detected deadlock in the vehicles' wait-for
+    /// graph by evicting the lower-priority vehicle's stale reservations.
+    fn release_held_by(&mut self, holder: &str) {
+        self.nodes.retain(|_, reservation| reservation.holder != holder);
+        self.edges.retain(|_, reservation| reservation.holder != holder);
+    }
+}
+
+/// Owns a group of [`VehicleSimulator`]s that share one node/edge graph and arbitrates their
+/// moves through a [`ReservationTable`] so that two AGVs never occupy the same node or traverse
+/// the same edge in opposing directions at the same time.
+///
+/// Cycles in the resulting wait-for graph (vehicle A waiting on a node held by B, B waiting on a
+/// node held by A) are detected and broken each tick by letting the vehicle with the
+/// lexicographically lowest serial number proceed while the other yields.
+pub struct FleetSimulator {
+    vehicles: Vec<VehicleSimulator>,
+    reservations: ReservationTable,
+    tick: u64,
+}
+
+impl FleetSimulator {
+    pub fn new(vehicles: Vec<VehicleSimulator>) -> Self {
+        Self {
+            vehicles,
+            reservations: ReservationTable::default(),
+            tick: 0,
+        }
+    }
+
+    pub fn vehicles(&self) -> &[VehicleSimulator] {
+        &self.vehicles
+    }
+
+    pub fn vehicles_mut(&mut self) -> &mut [VehicleSimulator] {
+        &mut self.vehicles
+    }
+
+    /// Advance every vehicle by one tick under traffic management, returning each vehicle's
+    /// [`TrafficStatus`] in the same order as [`Self::vehicles`].
+    pub fn tick(&mut self) -> Vec<TrafficStatus> {
+        self.tick += 1;
+
+        let mut statuses = Vec::with_capacity(self.vehicles.len());
+        for vehicle in &mut self.vehicles {
+            statuses.push(vehicle.update_state_traffic_aware(&mut self.reservations, self.tick));
+        }
+
+        self.break_deadlocks(&mut statuses);
+        statuses
+    }
+
+    /// Find every pair of vehicles waiting on each other (a 2-cycle in the wait-for graph) and
+    /// let the higher-priority one (lowest serial_number) proceed immediately by evicting the
+    /// lower-priority vehicle's reservations and re-running its tick.
+    fn break_deadlocks(&mut self, statuses: &mut [TrafficStatus]) {
+        for i in 0..self.vehicles.len() {
+            let TrafficStatus::WaitingOn(blocker) = &statuses[i] else {
+                continue;
+            };
+
+            let Some(j) = self
+                .vehicles
+                .iter()
+                .position(|vehicle| vehicle.serial_number() == blocker.as_str())
+            else {
+                continue;
+            };
+
+            let TrafficStatus::WaitingOn(blocker_of_j) = &statuses[j] else {
+                continue;
+            };
+            if blocker_of_j != self.vehicles[i].serial_number() {
+                continue;
+            }
+
+            // i and j wait on each other: a deadlock. Let the lower serial number win.
+            let (winner, loser) = if self.vehicles[i].serial_number() < self.vehicles[j].serial_number() {
+                (i, j)
+            } else {
+                (j, i)
+            };
+
+            self.reservations.release_held_by(self.vehicles[loser].serial_number());
+            statuses[winner] = self.vehicles[winner].update_state_traffic_aware(&mut self.reservations, self.tick);
+        }
+    }
+}
+
+/// Shared handle to a fleet of vehicles, held by every per-vehicle background task (MQTT
+/// dispatch, admin server, persistence) alongside the index of the vehicle it is responsible
+/// for, instead of each task owning an independent `Arc<Mutex<VehicleSimulator>>` of its own.
+/// This is what actually lets `FleetSimulator::tick`'s reservation-based traffic management run
+/// against the real vehicles a fleet publishes/persists/serves admin for, rather than only ever
+/// being constructed in a test.
+pub type SharedFleet = Arc<Mutex<FleetSimulator>>;
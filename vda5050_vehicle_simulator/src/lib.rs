@@ -0,0 +1,15 @@
+pub mod action_handlers;
+pub mod admin;
+pub mod config;
+pub mod fleet_simulator;
+pub mod mqtt_ack;
+pub mod mqtt_dispatcher;
+pub mod mqtt_handler;
+pub mod mqtt_transport;
+pub mod mqtt_utils;
+pub mod outbound_queue;
+pub mod persistence;
+pub mod protocol;
+pub mod tracer;
+pub mod utils;
+pub mod vehicle_simulator;
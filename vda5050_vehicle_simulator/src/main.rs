@@ -1,54 +1,207 @@
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
+use tokio::time::sleep;
 
-mod config;
-mod mqtt_utils;
-mod protocol;
-mod utils;
-mod vehicle_simulator;
-mod mqtt_handler;
+use vda5050_vehicle_simulator::admin::serve_admin;
+use vda5050_vehicle_simulator::config;
+use vda5050_vehicle_simulator::fleet_simulator::{FleetSimulator, SharedFleet};
+use vda5050_vehicle_simulator::mqtt_dispatcher::{MqttDispatcher, PublishSender};
+use vda5050_vehicle_simulator::persistence;
+use vda5050_vehicle_simulator::vehicle_simulator::VehicleSimulator;
 
-use vehicle_simulator::VehicleSimulator;
-use mqtt_handler::{subscribe_vda_messages, publish_vda_messages};
+/// Vehicles in a freshly spawned fleet are lined up this many distance units apart along the x
+/// axis instead of each independently randomizing its own starting position, so `robot_count`
+/// AGVs don't accidentally overlap at the same spot. Skipped for a vehicle that rehydrated a
+/// real position from persistence.
+const FLEET_SPACING: f32 = 3.0;
 
 #[tokio::main]
 async fn main() {
-    let config = crate::config::get_config();
+    let config = config::get_config();
+    let robot_count = config.settings.robot_count as usize;
 
-    for robot_index in 0..config.settings.robot_count {
-        spawn_vehicle_simulator(config.clone(), robot_index).await;
-    }
+    // Every vehicle in the fleet is built up front and handed to one shared `FleetSimulator`,
+    // instead of each living behind its own independent `Arc<Mutex<VehicleSimulator>>`, so the
+    // reservation-based traffic management `FleetSimulator::tick` provides actually arbitrates
+    // node/edge access between real vehicles in the running fleet rather than only ever being
+    // exercised in a test.
+    let vehicles: Vec<VehicleSimulator> = (0..config.settings.robot_count)
+        .map(|robot_index| build_vehicle(&config, robot_index))
+        .collect();
+    let fleet: SharedFleet = Arc::new(Mutex::new(FleetSimulator::new(vehicles)));
 
-    // Keep the main thread alive
-    loop {
-        tokio::time::sleep(Duration::from_millis(100)).await;
+    let mut dispatcher = MqttDispatcher::new(&config);
+    let mut publish_senders = Vec::with_capacity(robot_count);
+    for robot_index in 0..robot_count {
+        publish_senders.push(register_vehicle(&config, robot_index, Arc::clone(&fleet), &mut dispatcher).await);
     }
-}
 
-async fn spawn_vehicle_simulator(config: config::Config, robot_index: u32) {
-    // Create vehicle-specific configuration
-    let mut vehicle_config = config.clone();
-    vehicle_config.vehicle.serial_number = format!("{}{}", config.vehicle.serial_number, robot_index + 1);
-    
-    // Create and share vehicle simulator
-    let vehicle_simulator = VehicleSimulator::new(vehicle_config.clone());
-    let shared_simulator = Arc::new(Mutex::new(vehicle_simulator));
-    
-    // Clone for async tasks
-    let simulator_for_subscribe = Arc::clone(&shared_simulator);
-    let simulator_for_publish = Arc::clone(&shared_simulator);
-
-    // Spawn MQTT subscription task
-    tokio::spawn(subscribe_vda_messages(
-        vehicle_config,
-        simulator_for_subscribe,
-    ));
+    // One shared MQTT connection serves every registered vehicle, instead of each vehicle
+    // opening a subscribe/publish client pair of its own.
+    tokio::spawn(dispatcher.run());
 
-    // Spawn MQTT publishing task
-    tokio::spawn(publish_vda_messages(
-        simulator_for_publish,
+    tokio::spawn(run_fleet(
+        Arc::clone(&fleet),
+        publish_senders,
         config.settings.state_frequency,
         config.settings.visualization_frequency,
     ));
+
+    wait_for_shutdown(config.persistence, fleet, robot_count).await;
+}
+
+fn build_vehicle(config: &config::Config, robot_index: u32) -> VehicleSimulator {
+    let mut vehicle_config = config.clone();
+    vehicle_config.vehicle.serial_number = format!("{}{}", config.vehicle.serial_number, robot_index + 1);
+    vehicle_config.persistence.path = derive_persistence_path(&config.persistence.path, robot_index as usize);
+
+    let mut vehicle_simulator = VehicleSimulator::new(vehicle_config);
+    stagger_initial_position(&mut vehicle_simulator, robot_index);
+    vehicle_simulator
+}
+
+/// Derives the per-vehicle admin bind address from `bind_address`, offsetting its port by
+/// `vehicle_index` the same way `serial_number` is already suffixed, so `robot_count` vehicles'
+/// admin servers don't all try to bind the exact same address.
+fn derive_bind_address(bind_address: &str, vehicle_index: usize) -> String {
+    let (host, port) = bind_address.rsplit_once(':').expect("admin.bind_address must be host:port");
+    let port: u16 = port.parse().expect("admin.bind_address port must be numeric");
+    format!("{}:{}", host, port + vehicle_index as u16)
+}
+
+/// Derives the per-vehicle persistence path from `path`, suffixing the file stem by
+/// `vehicle_index` the same way `serial_number` is already suffixed, so `robot_count` vehicles'
+/// snapshots don't clobber each other on disk.
+fn derive_persistence_path(path: &str, vehicle_index: usize) -> String {
+    match path.rsplit_once('.') {
+        Some((stem, ext)) => format!("{}_{}.{}", stem, vehicle_index + 1, ext),
+        None => format!("{}_{}", path, vehicle_index + 1),
+    }
+}
+
+/// Registers the vehicle at `vehicle_index` within `fleet` with the shared dispatcher connection
+/// and, if enabled, spawns its admin server and periodic persistence.
+async fn register_vehicle(
+    config: &config::Config,
+    vehicle_index: usize,
+    fleet: SharedFleet,
+    dispatcher: &mut MqttDispatcher,
+) -> PublishSender {
+    let mut vehicle_config = config.clone();
+    vehicle_config.vehicle.serial_number = format!("{}{}", config.vehicle.serial_number, vehicle_index + 1);
+
+    let publish_tx = dispatcher.register(&vehicle_config, Arc::clone(&fleet), vehicle_index).await;
+
+    if config.admin.enabled {
+        let bind_address = derive_bind_address(&config.admin.bind_address, vehicle_index);
+        tokio::spawn(serve_admin(bind_address, Arc::clone(&fleet), vehicle_index));
+    }
+
+    if config.persistence.enabled {
+        let path = derive_persistence_path(&config.persistence.path, vehicle_index);
+        tokio::spawn(persistence::persist_periodically(
+            path,
+            Duration::from_secs(config.persistence.save_interval_secs),
+            Arc::clone(&fleet),
+            vehicle_index,
+        ));
+    }
+
+    publish_tx
+}
+
+/// Ticks the whole fleet forward together under `FleetSimulator::tick`'s reservation-based
+/// traffic management and hands each vehicle's state/visualization updates to the dispatcher as
+/// `PublishCommand`s over its entry in `publish_senders`, indexed the same way as `fleet`'s
+/// vehicles. Mirrors the per-vehicle tick/frequency bookkeeping a standalone connection would
+/// need, but ticks every vehicle in lockstep instead of independently, since the reservation
+/// table requires a consistent view of the whole fleet each tick.
+async fn run_fleet(fleet: SharedFleet, publish_senders: Vec<PublishSender>, state_frequency: u64, visualization_frequency: u64) {
+    // Bootstrap sequence mirrors `VehicleSimulator::publish_connection`: announce each
+    // just-connected vehicle's initial (broken) connection state, then flip to `Online` once the
+    // broker's had a moment to deliver it, so the fleet manager sees the transition rather than
+    // only the end state.
+    {
+        let mut fleet = fleet.lock().await;
+        for (vehicle_index, publish_tx) in publish_senders.iter().enumerate() {
+            let command = fleet.vehicles_mut()[vehicle_index].connection_command();
+            publish_tx.send(command).await.unwrap();
+        }
+    }
+    sleep(Duration::from_millis(1000)).await;
+    {
+        let mut fleet = fleet.lock().await;
+        for (vehicle_index, publish_tx) in publish_senders.iter().enumerate() {
+            let command = fleet.vehicles_mut()[vehicle_index].online_command();
+            publish_tx.send(command).await.unwrap();
+        }
+    }
+
+    let tick_time = 50;
+    let mut state_counter = 0;
+    let mut visualization_counter = 0;
+
+    loop {
+        {
+            let mut fleet = fleet.lock().await;
+            fleet.tick();
+
+            // Flush any MQTT5 request/response completion acks that became ready this tick, so a
+            // master control that asked for one learns an action finished/failed without having
+            // to poll the `state` topic.
+            for (vehicle_index, publish_tx) in publish_senders.iter().enumerate() {
+                for ack_command in fleet.vehicles_mut()[vehicle_index].drain_completion_acks() {
+                    publish_tx.send(ack_command).await.unwrap();
+                }
+            }
+
+            state_counter += 1;
+            if state_counter * tick_time > 1000 / state_frequency {
+                state_counter = 0;
+                for (vehicle_index, publish_tx) in publish_senders.iter().enumerate() {
+                    let command = fleet.vehicles_mut()[vehicle_index].state_command();
+                    publish_tx.send(command).await.unwrap();
+                }
+            }
+
+            visualization_counter += 1;
+            if visualization_counter * tick_time > 1000 / visualization_frequency {
+                visualization_counter = 0;
+                for (vehicle_index, publish_tx) in publish_senders.iter().enumerate() {
+                    let command = fleet.vehicles_mut()[vehicle_index].visualization_command();
+                    publish_tx.send(command).await.unwrap();
+                }
+            }
+        }
+
+        sleep(Duration::from_millis(tick_time)).await;
+    }
+}
+
+/// Lines a freshly spawned vehicle up along the fleet's x axis instead of letting it randomize
+/// its own starting position independently. A no-op if the vehicle already rehydrated a real
+/// `position_initialized` position from persistence.
+fn stagger_initial_position(simulator: &mut VehicleSimulator, robot_index: u32) {
+    if let Some(position) = simulator.state.agv_position.as_mut() {
+        if !position.position_initialized {
+            position.x = robot_index as f32 * FLEET_SPACING;
+            position.y = 0.0;
+        }
+    }
+}
+
+/// Shared shutdown path for the whole fleet: wait for a Ctrl-C/SIGINT, then (if persistence is
+/// enabled) persist every vehicle's state one last time before the process exits, so a clean
+/// shutdown never loses progress made since the last periodic save.
+async fn wait_for_shutdown(persistence_config: config::PersistenceConfig, fleet: SharedFleet, robot_count: usize) {
+    tokio::signal::ctrl_c().await.expect("failed to listen for the shutdown signal");
+    println!("Shutting down fleet of {} vehicle(s)...", robot_count);
+
+    if persistence_config.enabled {
+        for vehicle_index in 0..robot_count {
+            let path = derive_persistence_path(&persistence_config.path, vehicle_index);
+            persistence::persist_now(&path, &fleet, vehicle_index).await;
+        }
+    }
 }
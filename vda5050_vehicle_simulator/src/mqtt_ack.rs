@@ -0,0 +1,67 @@
+//! MQTT5 request/response acknowledgement for `instantActions` messages, using the
+//! `ResponseTopic`/`CorrelationData` properties a master control can set on its request to ask
+//! for a direct, per-message confirmation instead of polling the `state` topic. This is the
+//! request-id/correlation-data technique MQTT5 configuration tools use.
+
+use paho_mqtt as mqtt;
+use paho_mqtt::PropertyCode;
+use serde::Serialize;
+
+use crate::mqtt_dispatcher::PublishCommand;
+
+/// Where to route an acknowledgement, read off an incoming message's MQTT5 properties.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponseTarget {
+    pub topic: String,
+    pub correlation_data: Vec<u8>,
+}
+
+impl ResponseTarget {
+    /// Reads the `ResponseTopic`/`CorrelationData` MQTT5 properties off `msg`, if both are
+    /// present. A v3.1.1 connection never carries MQTT5 properties, so this is `None` whenever
+    /// the sender didn't ask for (or isn't using MQTT5 for) a direct ack — the common case is
+    /// left untouched rather than failing.
+    pub fn from_message(msg: &mqtt::Message) -> Option<Self> {
+        let props = msg.properties();
+        let topic = props.get_string(PropertyCode::ResponseTopic)?;
+        let correlation_data = props.get_binary(PropertyCode::CorrelationData)?;
+        Some(Self { topic, correlation_data })
+    }
+}
+
+/// Accept/reject/completion status reported for an acknowledged `instantActions` message.
+/// `Rejected` is reserved for a future validation layer: the simulator currently accepts every
+/// instant action it's given, so only `Accepted` is produced at receipt time today.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AckStatus {
+    Accepted,
+    Rejected,
+    Finished,
+    Failed,
+}
+
+/// Acknowledgement payload published to a request's [`ResponseTarget`]: which action_ids the ack
+/// covers, and their accept/reject or (for a later completion ack) finished/failed status.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstantActionsAck {
+    pub action_ids: Vec<String>,
+    pub status: AckStatus,
+}
+
+impl InstantActionsAck {
+    /// Builds the [`PublishCommand`] for this ack, with `target.correlation_data` echoed back
+    /// via the MQTT5 `CorrelationData` property so the requester can match it to its request.
+    /// Published at QoS 1: an ack silently getting dropped would defeat the point of asking for
+    /// one.
+    pub fn into_command(self, target: &ResponseTarget) -> PublishCommand {
+        PublishCommand {
+            topic: target.topic.clone(),
+            payload: serde_json::to_string(&self).unwrap(),
+            qos: mqtt::QOS_1,
+            retain: false,
+            correlation_data: Some(target.correlation_data.clone()),
+        }
+    }
+}
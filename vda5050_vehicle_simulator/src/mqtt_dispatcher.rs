@@ -0,0 +1,266 @@
+//! Centralized MQTT connection shared by every simulated vehicle in the process, instead of each
+//! `VehicleSimulator` opening a subscribe client and a publish client of its own. A single
+//! `AsyncClient` subscribes to every registered vehicle's `order`/`instantActions` topics at
+//! once; inbound messages are demuxed by the `manufacturer/serial_number` pair parsed out of the
+//! topic and routed to the matching simulator, while vehicles hand their outbound
+//! state/visualization/connection updates back as [`PublishCommand`]s over an `mpsc` channel for
+//! the dispatcher to actually put on the wire. This turns per-robot connection count from O(2N)
+//! into O(1).
+//!
+//! A single MQTT connection only has one Last-Will-and-Testament slot, so the shared connection
+//! itself carries no will at all; instead each registered vehicle gets its own dedicated,
+//! subscription-free sentinel connection (see `mqtt_handler::maintain_lwt_connection`) whose sole
+//! job is to carry that vehicle's `ConnectionBroken` will, so a full process crash is still
+//! reported per-vehicle rather than only for whichever vehicle registered first.
+
+use futures_util::StreamExt;
+use paho_mqtt as mqtt;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+
+use crate::config;
+use crate::fleet_simulator::SharedFleet;
+use crate::mqtt_handler::{self, VehicleRef};
+use crate::mqtt_transport::{self, ReconnectOutcome};
+use crate::mqtt_utils;
+use crate::outbound_queue::OutboundQueue;
+
+/// One message a `VehicleSimulator` wants put on the wire, built by pure (non-IO) methods on
+/// `VehicleSimulator` and routed through the dispatcher's shared connection instead of a client
+/// of its own.
+#[derive(Clone, Debug)]
+pub struct PublishCommand {
+    pub topic: String,
+    pub payload: String,
+    pub qos: i32,
+    pub retain: bool,
+    /// MQTT5 `CorrelationData` to echo back verbatim, set only on `mqtt_ack`'s instantActions
+    /// acknowledgement commands; `None` for every other kind of publish (state, visualization,
+    /// connection).
+    pub correlation_data: Option<Vec<u8>>,
+}
+
+impl PublishCommand {
+    /// Builds the outgoing `mqtt::Message`, attaching `correlation_data` as an MQTT5 property
+    /// when set.
+    fn build_message(&self) -> mqtt::Message {
+        let mut builder = mqtt::MessageBuilder::new()
+            .topic(&self.topic)
+            .payload(self.payload.clone())
+            .qos(self.qos)
+            .retained(self.retain);
+
+        if let Some(correlation_data) = &self.correlation_data {
+            let mut props = mqtt::Properties::new();
+            props
+                .push_binary(mqtt::PropertyCode::CorrelationData, correlation_data.clone())
+                .unwrap();
+            builder = builder.properties(props);
+        }
+
+        builder.finalize()
+    }
+
+    /// Fire-and-forget publish, appropriate for a loss-tolerant command (e.g. `visualization`).
+    pub fn publish(&self, mqtt_cli: &mqtt::AsyncClient) {
+        if self.correlation_data.is_some() {
+            mqtt_cli.publish(self.build_message());
+        } else {
+            mqtt_utils::mqtt_publish(mqtt_cli, &self.topic, &self.payload, self.qos, self.retain);
+        }
+    }
+
+    /// Publish and await the broker's acknowledgement, appropriate where silently dropping the
+    /// update would matter (e.g. `state`, `connection`, an instantActions ack).
+    pub async fn publish_confirmed(&self, mqtt_cli: &mqtt::AsyncClient) {
+        if self.correlation_data.is_some() {
+            mqtt_cli.publish(self.build_message()).await.unwrap();
+        } else {
+            mqtt_utils::mqtt_publish_confirmed(mqtt_cli, &self.topic, &self.payload, self.qos, self.retain)
+                .await
+                .unwrap();
+        }
+    }
+}
+
+/// Channel handle each vehicle's tick loop holds to hand its outbound publishes to the
+/// dispatcher.
+pub type PublishSender = mpsc::Sender<PublishCommand>;
+
+/// Keys a registered vehicle by the `manufacturer/serial_number` pair parsed out of its MQTT
+/// topics, so an inbound message can be routed back to the right simulator.
+pub fn vehicle_key(manufacturer: &str, serial_number: &str) -> String {
+    format!("{}/{}", manufacturer, serial_number)
+}
+
+/// Pulls the `manufacturer/serial_number` pair out of a VDA5050 topic of the form
+/// `{interface}/{version}/{manufacturer}/{serial_number}/{messageType}`.
+pub fn vehicle_key_from_topic(topic: &str) -> Option<String> {
+    let segments: Vec<&str> = topic.split('/').collect();
+    Some(vehicle_key(segments.get(2)?, segments.get(3)?))
+}
+
+/// How many outstanding [`PublishCommand`]s the dispatcher will buffer from vehicles before a
+/// `send` starts applying backpressure.
+const PUBLISH_QUEUE_DEPTH: usize = 256;
+
+/// Owns the single MQTT connection shared by every vehicle registered with it.
+pub struct MqttDispatcher {
+    client: mqtt::AsyncClient,
+    vehicles: HashMap<String, (SharedFleet, usize)>,
+    topics: Vec<String>,
+    qos: Vec<i32>,
+    publish_tx: PublishSender,
+    publish_rx: mpsc::Receiver<PublishCommand>,
+    /// `true` once the shared connection is up and publishes can go straight to the broker;
+    /// `false` while disconnected, when outbound commands are buffered in `outbound_queue`
+    /// instead (see `outbound_queue::OutboundQueue`).
+    connected: bool,
+    outbound_queue: OutboundQueue,
+    /// `MqttBrokerConfig::protocol_version`, kept on the dispatcher so `run`/`handle_connection_loss`
+    /// can pass it to `mqtt_handler::build_connect_opts` instead of reaching for global config.
+    protocol_version: String,
+}
+
+impl MqttDispatcher {
+    pub fn new(config: &config::Config) -> Self {
+        let (publish_tx, publish_rx) = mpsc::channel(PUBLISH_QUEUE_DEPTH);
+
+        Self {
+            client: mqtt_handler::create_mqtt_client(),
+            vehicles: HashMap::new(),
+            topics: Vec::new(),
+            qos: Vec::new(),
+            publish_tx,
+            publish_rx,
+            connected: false,
+            outbound_queue: OutboundQueue::new(&config.outbound_queue),
+            protocol_version: config.mqtt_broker.protocol_version.clone(),
+        }
+    }
+
+    /// Registers the vehicle at `vehicle_index` within `fleet` with the dispatcher: its
+    /// `order`/`instantActions` topics are added to the shared subscription list, and its
+    /// manufacturer/serial_number become its routing key for inbound messages. Returns the
+    /// [`PublishSender`] the vehicle's tick loop should use to publish over the shared
+    /// connection. Since the shared connection can only ever carry one Last-Will, this also
+    /// spawns the vehicle its own dedicated sentinel connection (see
+    /// `mqtt_handler::maintain_lwt_connection`) so a process crash is still reported for this
+    /// vehicle specifically, not just whichever vehicle registered first.
+    pub async fn register(
+        &mut self,
+        config: &config::Config,
+        fleet: SharedFleet,
+        vehicle_index: usize,
+    ) -> PublishSender {
+        let base_topic = mqtt_utils::generate_vda_mqtt_base_topic(
+            &config.mqtt_broker.vda_interface,
+            &config.vehicle.vda_version,
+            &config.vehicle.manufacturer,
+            &config.vehicle.serial_number,
+        );
+        self.topics.push(format!("{}/order", base_topic));
+        self.qos.push(1);
+        self.topics.push(format!("{}/instantActions", base_topic));
+        self.qos.push(1);
+
+        let last_will = fleet.lock().await.vehicles()[vehicle_index].last_will();
+        let backoff = mqtt_transport::BackoffConfig::from_config(&config.mqtt_broker);
+        tokio::spawn(mqtt_handler::maintain_lwt_connection(
+            last_will,
+            backoff,
+            config.mqtt_broker.protocol_version.clone(),
+        ));
+
+        self.vehicles.insert(
+            vehicle_key(&config.vehicle.manufacturer, &config.vehicle.serial_number),
+            (fleet, vehicle_index),
+        );
+
+        self.publish_tx.clone()
+    }
+
+    /// Connects the shared client, subscribes to every registered vehicle's topics, then drains
+    /// inbound demuxing and outbound publishing concurrently until the process exits.
+    pub async fn run(mut self) {
+        mqtt_handler::connect_to_broker(&self.client, None, &self.protocol_version).await;
+        mqtt_handler::subscribe_to_topics(&self.client, &self.topics, &self.qos).await;
+        self.connected = true;
+
+        let mut message_stream = self.client.get_stream(25 * self.vehicles.len().max(1) as i32);
+        let backoff = mqtt_transport::BackoffConfig::from_config(&config::get_config().mqtt_broker);
+
+        loop {
+            tokio::select! {
+                msg_opt = message_stream.next() => {
+                    match msg_opt {
+                        Some(Some(msg)) => self.route_incoming(msg).await,
+                        Some(None) => self.handle_connection_loss(backoff).await,
+                        None => break,
+                    }
+                }
+                Some(command) = self.publish_rx.recv() => {
+                    if self.connected {
+                        command.publish_confirmed(&self.client).await;
+                    } else {
+                        self.outbound_queue.buffer(command);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Routes one incoming message to the vehicle it's addressed to, based on the
+    /// `manufacturer/serial_number` parsed from its topic.
+    async fn route_incoming(&self, msg: mqtt::Message) {
+        let Some(key) = vehicle_key_from_topic(msg.topic()) else {
+            println!("Could not parse a vehicle from topic: {}", msg.topic());
+            return;
+        };
+
+        let Some((fleet, vehicle_index)) = self.vehicles.get(&key) else {
+            println!("No registered vehicle for topic: {}", msg.topic());
+            return;
+        };
+
+        mqtt_handler::handle_incoming_message(msg, &self.client, VehicleRef::Fleet(fleet, *vehicle_index)).await;
+    }
+
+    /// Reconnects and resubscribes the shared connection, then flushes whatever was buffered in
+    /// `outbound_queue` while disconnected, and finally resyncs every registered vehicle:
+    /// re-announces `Online` and force-publishes state/visualization so none of them are left
+    /// showing stale, pre-outage data to the fleet manager even if nothing was buffered.
+    async fn handle_connection_loss(&mut self, backoff: mqtt_transport::BackoffConfig) {
+        println!("Dispatcher lost connection. Attempting to reconnect...");
+        self.connected = false;
+
+        let connect = || async {
+            self.client.connect(mqtt_handler::build_connect_opts(None, &self.protocol_version)).await.map(|_| ())
+        };
+        let subscribe = || async { self.client.subscribe_many(&self.topics, &self.qos).await.map(|_| ()) };
+
+        let outcome = mqtt_transport::reconnect_with_backoff(backoff, connect, subscribe, tokio::time::sleep).await;
+
+        match outcome {
+            ReconnectOutcome::Reconnected { attempts } => {
+                println!("Dispatcher reconnected to MQTT broker after {} attempt(s)", attempts);
+                self.connected = true;
+
+                for command in self.outbound_queue.drain() {
+                    command.publish_confirmed(&self.client).await;
+                }
+
+                for (fleet, vehicle_index) in self.vehicles.values() {
+                    let mut fleet = fleet.lock().await;
+                    let simulator = &mut fleet.vehicles_mut()[*vehicle_index];
+                    simulator.republish_online(&self.client).await;
+                    simulator.publish_state(&self.client).await;
+                    simulator.publish_visualization(&self.client).await;
+                }
+            }
+            ReconnectOutcome::Exhausted { attempts } => {
+                println!("Dispatcher failed to reconnect to MQTT broker after {} attempt(s)", attempts);
+            }
+        }
+    }
+}
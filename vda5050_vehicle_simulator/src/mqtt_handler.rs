@@ -1,120 +1,88 @@
 use futures_util::StreamExt;
 use paho_mqtt as mqtt;
-use std::sync::Arc;
-use std::{process, time::Duration};
-use tokio::sync::Mutex;
+use std::process;
 
-use crate::config;
+use crate::fleet_simulator::SharedFleet;
+use crate::mqtt_ack::{AckStatus, InstantActionsAck, ResponseTarget};
+use crate::mqtt_transport::{self, ReconnectOutcome};
 use crate::mqtt_utils;
 use crate::utils;
 use crate::vehicle_simulator::VehicleSimulator;
-use crate::protocol::vda_2_0_0::vda5050_2_0_0_order::Order;
-use crate::protocol::vda_2_0_0::vda5050_2_0_0_instant_actions::InstantActions;
+use crate::protocol::version;
 
-pub async fn subscribe_vda_messages(
-    config: config::Config, 
-    simulator: Arc<Mutex<VehicleSimulator>>
-) {
-    let base_topic = format!(
-        "{}/{}/{}/{}",
-        config.mqtt_broker.vda_interface,
-        config.vehicle.vda_version,
-        config.vehicle.manufacturer,
-        config.vehicle.serial_number,
-    );
-
-    let topics = vec![
-        format!("{}/order", base_topic),
-        format!("{}/instantActions", base_topic),
-    ];
-
-    if topics.is_empty() {
-        println!("Error: No topics specified!");
-        process::exit(-1);
-    }
-
-    let qos = vec![1; topics.len()];
-    let mut mqtt_client = create_mqtt_client();
-    let mut message_stream = mqtt_client.get_stream(25);
-
-    connect_to_broker(&mqtt_client).await;
-    subscribe_to_topics(&mqtt_client, &topics, &qos).await;
-
-    println!("Waiting for messages on topics: {:?}", topics);
-    
-    while let Some(msg_opt) = message_stream.next().await {
-        if let Some(msg) = msg_opt {
-            handle_incoming_message(msg, &simulator).await;
-        } else {
-            handle_connection_loss(&mqtt_client).await;
-        }
-    }
+/// A vehicle a message can be routed to: one member of a [`SharedFleet`] behind the centralized
+/// `MqttDispatcher`. Lets [`handle_incoming_message`] and friends stay agnostic to which fleet
+/// slot they're addressing.
+pub(crate) enum VehicleRef<'a> {
+    Fleet(&'a SharedFleet, usize),
 }
 
-pub async fn publish_vda_messages(
-    simulator: Arc<Mutex<VehicleSimulator>>,
-    state_frequency: u64,
-    visualization_frequency: u64,
-) {
-    let mqtt_client = create_mqtt_client();
-    connect_to_broker(&mqtt_client).await;
-
-    // Publish initial connection
-    simulator.lock().await.publish_connection(&mqtt_client).await;
-
-    // Main publishing loop
-    let tick_time = 50;
-    let mut state_counter = 0;
-    let mut visualization_counter = 0;
-
-    loop {
-        simulator.lock().await.update_state();
-
-        // Publish state at specified frequency
-        state_counter += 1;
-        if state_counter * tick_time > 1000 / state_frequency {
-            state_counter = 0;
-            simulator.lock().await.publish_state(&mqtt_client).await;
+impl VehicleRef<'_> {
+    async fn with_mut<R>(&self, f: impl FnOnce(&mut VehicleSimulator) -> R) -> R {
+        match self {
+            VehicleRef::Fleet(fleet, vehicle_index) => f(&mut fleet.lock().await.vehicles_mut()[*vehicle_index]),
         }
-
-        // Publish visualization at specified frequency
-        visualization_counter += 1;
-        if visualization_counter * tick_time > 1000 / visualization_frequency {
-            visualization_counter = 0;
-            simulator.lock().await.publish_visualization(&mqtt_client).await;
-        }
-
-        tokio::time::sleep(Duration::from_millis(tick_time)).await;
     }
 }
 
-fn create_mqtt_client() -> mqtt::AsyncClient {
+pub(crate) fn create_mqtt_client() -> mqtt::AsyncClient {
     mqtt::AsyncClient::new(mqtt_utils::mqtt_create_opts()).unwrap_or_else(|e| {
         println!("Error creating MQTT client: {:?}", e);
         process::exit(-1);
     })
 }
 
-async fn connect_to_broker(mqtt_client: &mqtt::AsyncClient) {
-    let conn_opts = mqtt::ConnectOptionsBuilder::with_mqtt_version(mqtt::MQTT_VERSION_5)
-        .clean_start(true)
-        .finalize();
+/// Maps `MqttBrokerConfig.protocol_version` to the paho_mqtt version constant, defaulting to
+/// v3.1.1 for anything other than an explicit `"5"` so an unset/typo'd config value doesn't
+/// silently request a broker feature set the user didn't ask for.
+fn mqtt_protocol_version(protocol_version: &str) -> u32 {
+    match protocol_version {
+        "5" => mqtt::MQTT_VERSION_5,
+        _ => mqtt::MQTT_VERSION_3_1_1,
+    }
+}
 
+/// Builds the options passed to every `connect`/reconnect attempt. `last_will` is registered so
+/// the broker publishes it (retained `CONNECTIONBROKEN`) itself if this vehicle drops off
+/// ungracefully, rather than relying on the vehicle to announce its own disconnection.
+/// `protocol_version` is `MqttBrokerConfig::protocol_version` as a parameter rather than read
+/// from global config, since every caller already has a `&Config`/`&MqttBrokerConfig` in scope.
+pub(crate) fn build_connect_opts(last_will: Option<mqtt::Message>, protocol_version: &str) -> mqtt::ConnectOptions {
+    let protocol_version = mqtt_protocol_version(protocol_version);
+    let mut builder = mqtt::ConnectOptionsBuilder::with_mqtt_version(protocol_version);
+    builder.clean_start(true);
+    if let Some(will) = last_will {
+        builder.will_message(will);
+    }
+    builder.finalize()
+}
+
+pub(crate) async fn connect_to_broker(
+    mqtt_client: &mqtt::AsyncClient,
+    last_will: Option<mqtt::Message>,
+    protocol_version: &str,
+) {
+    let conn_opts = build_connect_opts(last_will, protocol_version);
     mqtt_client.connect(conn_opts).await.unwrap();
 }
 
-async fn subscribe_to_topics(
-    mqtt_client: &mqtt::AsyncClient, 
-    topics: &[String], 
+pub(crate) async fn subscribe_to_topics(
+    mqtt_client: &mqtt::AsyncClient,
+    topics: &[String],
     qos: &[i32]
 ) {
     println!("Subscribing to topics: {:?}", topics);
     mqtt_client.subscribe_many(topics, qos).await.unwrap();
 }
 
-async fn handle_incoming_message(
-    msg: mqtt::Message, 
-    simulator: &Arc<Mutex<VehicleSimulator>>
+/// Dispatches one incoming message to the vehicle `vehicle` it's addressed to, based on its
+/// VDA5050 message type (`order`/`instantActions`/unknown) parsed from the topic. `mqtt_cli` is
+/// only used to publish an immediate MQTT5 request/response ack for an `instantActions` message
+/// that asks for one; every other message type ignores it.
+pub(crate) async fn handle_incoming_message(
+    msg: mqtt::Message,
+    mqtt_cli: &mqtt::AsyncClient,
+    vehicle: VehicleRef<'_>,
 ) {
     if msg.retained() {
         print!("(R) ");
@@ -122,44 +90,104 @@ async fn handle_incoming_message(
 
     let topic = msg.topic();
     let topic_type = utils::get_topic_type(topic);
+    let response_target = ResponseTarget::from_message(&msg);
     let payload = String::from_utf8_lossy(msg.payload()).to_string();
 
     match topic_type.as_ref() {
-        "order" => handle_order_message(&payload, simulator).await,
-        "instantActions" => handle_instant_actions_message(&payload, simulator).await,
+        "order" => handle_order_message(&payload, &vehicle).await,
+        "instantActions" => {
+            handle_instant_actions_message(&payload, mqtt_cli, response_target, &vehicle).await
+        }
         _ => println!("Unknown topic type: {}", topic_type),
     }
 }
 
-async fn handle_order_message(payload: &str, simulator: &Arc<Mutex<VehicleSimulator>>) {
-    match serde_json::from_str::<Order>(payload) {
-        Ok(order) => {
-            simulator.lock().await.process_order(order);
-        }
-        Err(e) => {
-            println!("Error parsing order message: {}", e);
-        }
+async fn handle_order_message(payload: &str, vehicle: &VehicleRef<'_>) {
+    vehicle
+        .with_mut(|simulator| match version::decode_order(payload, simulator.vda_full_version()) {
+            Ok(order) => simulator.process_order(order),
+            Err(e) => println!("Error parsing order message: {}", e),
+        })
+        .await;
+}
+
+/// Decodes and accepts an `instantActions` message, then, if it carried MQTT5
+/// `ResponseTopic`/`CorrelationData` properties, immediately acks every action_id as `Accepted`
+/// and registers each for a later completion ack once it reaches `Finished`/`Failed` (see
+/// `VehicleSimulator::drain_completion_acks`).
+async fn handle_instant_actions_message(
+    payload: &str,
+    mqtt_cli: &mqtt::AsyncClient,
+    response_target: Option<ResponseTarget>,
+    vehicle: &VehicleRef<'_>,
+) {
+    let pending_ack = vehicle
+        .with_mut(move |simulator| match version::decode_instant_actions(payload, simulator.vda_full_version()) {
+            Ok(instant_actions) => {
+                let action_ids: Vec<String> =
+                    instant_actions.actions.iter().map(|action| action.action_id.clone()).collect();
+                simulator.accept_instant_actions(instant_actions);
+
+                response_target.map(|target| {
+                    for action_id in &action_ids {
+                        simulator.register_pending_ack(action_id.clone(), target.clone());
+                    }
+                    (InstantActionsAck { action_ids, status: AckStatus::Accepted }, target)
+                })
+            }
+            Err(e) => {
+                println!("Error parsing instant actions message: {}", e);
+                None
+            }
+        })
+        .await;
+
+    if let Some((ack, target)) = pending_ack {
+        ack.into_command(&target).publish_confirmed(mqtt_cli).await;
     }
 }
 
-async fn handle_instant_actions_message(payload: &str, simulator: &Arc<Mutex<VehicleSimulator>>) {
-    match serde_json::from_str::<InstantActions>(payload) {
-        Ok(instant_actions) => {
-            simulator.lock().await.accept_instant_actions(instant_actions);
+/// Keeps one dedicated, subscription-free MQTT connection alive for the sole purpose of carrying
+/// `last_will` as its Last-Will-and-Testament, so the broker has somewhere to publish this
+/// specific vehicle's `ConnectionBroken` if the whole process dies. A connection shared by the
+/// whole fleet (`MqttDispatcher`) only has one Last-Will slot, so without this every vehicle but
+/// the first-registered would stay reported `Online` forever after a crash. Reconnects under the
+/// same backoff policy as every other MQTT connection in the simulator if this connection itself
+/// drops. `protocol_version` is owned rather than borrowed since this future is handed to
+/// `tokio::spawn` and must not borrow from its caller.
+pub(crate) async fn maintain_lwt_connection(
+    last_will: mqtt::Message,
+    backoff: mqtt_transport::BackoffConfig,
+    protocol_version: String,
+) {
+    let client = create_mqtt_client();
+    connect_to_broker(&client, Some(last_will.clone()), &protocol_version).await;
+
+    loop {
+        let mut message_stream = client.get_stream(1);
+        while message_stream.next().await.is_some() {
+            // This connection never subscribes to anything, so no message is ever expected here;
+            // only the stream closing (`None`, below) means the connection dropped.
         }
-        Err(e) => {
-            println!("Error parsing instant actions message: {}", e);
+
+        let connect = || {
+            let last_will = last_will.clone();
+            async { client.connect(build_connect_opts(Some(last_will), &protocol_version)).await.map(|_| ()) }
+        };
+        let subscribe = || async { Ok(()) };
+
+        match mqtt_transport::reconnect_with_backoff(backoff, connect, subscribe, tokio::time::sleep).await {
+            ReconnectOutcome::Reconnected { attempts } => {
+                println!("LWT connection reconnected after {} attempt(s)", attempts);
+            }
+            ReconnectOutcome::Exhausted { attempts } => {
+                println!(
+                    "LWT connection failed to reconnect after {} attempt(s); this vehicle will no longer be \
+                     reported offline if the process crashes",
+                    attempts
+                );
+                return;
+            }
         }
     }
 }
-
-async fn handle_connection_loss(mqtt_client: &mqtt::AsyncClient) {
-    println!("Lost connection. Attempting to reconnect...");
-    
-    while let Err(err) = mqtt_client.reconnect().await {
-        println!("Error reconnecting: {}", err);
-        tokio::time::sleep(Duration::from_millis(1000)).await;
-    }
-    
-    println!("Successfully reconnected to MQTT broker");
-} 
\ No newline at end of file
@@ -0,0 +1,91 @@
+use std::future::Future;
+use std::time::Duration;
+
+use crate::config::MqttBrokerConfig;
+
+/// Bounds for [`reconnect_with_backoff`]'s delay schedule and attempt budget, sourced from
+/// `MqttBrokerConfig` so deployments can tune reconnect behavior without a rebuild.
+#[derive(Clone, Copy, Debug)]
+pub struct BackoffConfig {
+    /// Base delay before the first retry following a failed reconnect attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound the exponential backoff is clamped to, so a long outage doesn't end up
+    /// retrying only once an hour.
+    pub max_backoff: Duration,
+    /// Number of reconnect attempts to make before giving up.
+    pub max_attempts: u32,
+}
+
+impl BackoffConfig {
+    /// Builds a `BackoffConfig` from the reconnect fields of `MqttBrokerConfig`.
+    pub fn from_config(config: &MqttBrokerConfig) -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(config.reconnect_initial_backoff_ms),
+            max_backoff: Duration::from_secs(config.reconnect_max_backoff_secs),
+            max_attempts: config.reconnect_max_attempts,
+        }
+    }
+}
+
+/// Outcome of one [`reconnect_with_backoff`] pass, surfaced so callers (and integration tests)
+/// can observe whether the broker connection and topic subscriptions were actually restored,
+/// without needing a real broker to simulate a dropped connection against.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReconnectOutcome {
+    /// The broker connection and topic subscriptions were both re-established.
+    Reconnected { attempts: u32 },
+    /// `max_attempts` tries were made and every one failed to connect and/or subscribe.
+    Exhausted { attempts: u32 },
+}
+
+/// Deterministic exponential backoff delay before the `attempt`-th retry (0-indexed), doubling
+/// each time up to `max_backoff`, before jitter is applied. Exposed separately from
+/// [`backoff_duration`] so callers/tests can reason about the un-jittered envelope a delay must
+/// fall within.
+pub fn backoff_ceiling(attempt: u32, initial_backoff: Duration, max_backoff: Duration) -> Duration {
+    let factor = 1u64 << attempt.min(16);
+    let millis = (initial_backoff.as_millis() as u64).saturating_mul(factor);
+    Duration::from_millis(millis.min(max_backoff.as_millis() as u64))
+}
+
+/// Delay to wait before the `attempt`-th retry (0-indexed): the exponential backoff envelope from
+/// [`backoff_ceiling`], randomized down to as little as half of itself ("equal jitter"), so a
+/// fleet of vehicles (or multiple dispatcher processes) reconnecting after the same broker outage
+/// don't all retry in lockstep at the exact same instants.
+pub fn backoff_duration(attempt: u32, initial_backoff: Duration, max_backoff: Duration) -> Duration {
+    let ceiling = backoff_ceiling(attempt, initial_backoff, max_backoff);
+    let jitter_fraction: f64 = rand::random();
+    Duration::from_millis((ceiling.as_millis() as f64 * (0.5 + 0.5 * jitter_fraction)).round() as u64)
+}
+
+/// Drives broker reconnection with exponential backoff: tries `connect`, and on success
+/// `subscribe`, retrying with a growing delay between attempts until either both succeed or
+/// `backoff.max_attempts` is exhausted. `connect` and `subscribe` are injected so production code
+/// can pass real MQTT calls while tests pass fakes that simulate a dropped connection.
+pub async fn reconnect_with_backoff<ConnectFut, SubscribeFut, SleepFut, E>(
+    backoff: BackoffConfig,
+    mut connect: impl FnMut() -> ConnectFut,
+    mut subscribe: impl FnMut() -> SubscribeFut,
+    mut sleep: impl FnMut(Duration) -> SleepFut,
+) -> ReconnectOutcome
+where
+    ConnectFut: Future<Output = Result<(), E>>,
+    SubscribeFut: Future<Output = Result<(), E>>,
+    SleepFut: Future<Output = ()>,
+{
+    for attempt in 0..backoff.max_attempts {
+        if attempt > 0 {
+            sleep(backoff_duration(attempt - 1, backoff.initial_backoff, backoff.max_backoff)).await;
+        }
+
+        if connect().await.is_ok() && subscribe().await.is_ok() {
+            return ReconnectOutcome::Reconnected {
+                attempts: attempt + 1,
+            };
+        }
+    }
+
+    ReconnectOutcome::Exhausted {
+        attempts: backoff.max_attempts,
+    }
+}
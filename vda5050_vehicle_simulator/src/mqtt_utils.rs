@@ -22,12 +22,33 @@ pub fn mqtt_create_opts() -> mqtt::CreateOptions {
     return create_opts;
 }
 
-pub async fn mqtt_publish(mqtt_cli: &mqtt::AsyncClient, topic: &str, data: &str) -> mqtt::Result<()> {
+/// Hand a message off to the client without waiting for broker acknowledgement. The publish
+/// itself is initiated synchronously by `AsyncClient::publish`; dropping the returned
+/// `DeliveryToken` here just means nobody awaits its delivery confirmation. Appropriate for
+/// high-frequency, loss-tolerant topics (e.g. `visualization`).
+pub fn mqtt_publish(mqtt_cli: &mqtt::AsyncClient, topic: &str, data: &str, qos: i32, retained: bool) -> mqtt::DeliveryToken {
     let json: serde_json::Value = serde_json::from_str(data).unwrap();
     let payload = serde_json::to_vec(&json).unwrap();
-    let msg = mqtt::Message::new(topic, payload, mqtt::QOS_1);
-    mqtt_cli.publish(msg).await?;
-    Ok(())
+    let msg = mqtt::MessageBuilder::new()
+        .topic(topic)
+        .payload(payload)
+        .qos(qos)
+        .retained(retained)
+        .finalize();
+    mqtt_cli.publish(msg)
+}
+
+/// Like [`mqtt_publish`], but awaits the `DeliveryToken` so the caller learns whether the
+/// broker actually accepted the message. Appropriate for topics where silently dropping an
+/// update would matter (e.g. `state`, `connection`).
+pub async fn mqtt_publish_confirmed(
+    mqtt_cli: &mqtt::AsyncClient,
+    topic: &str,
+    data: &str,
+    qos: i32,
+    retained: bool,
+) -> mqtt::Result<()> {
+    mqtt_publish(mqtt_cli, topic, data, qos, retained).await
 }
 
 pub fn generate_vda_mqtt_base_topic(
@@ -0,0 +1,105 @@
+//! Bounded buffer for `PublishCommand`s that couldn't go out while the MQTT connection was down,
+//! so a broker outage doesn't leave the fleet manager jumping discontinuously once it reconnects.
+//! Follows the durable-session/persistence model brokers themselves use for queued messages.
+//!
+//! Two lanes, chosen by the convention `mqtt_dispatcher` already topics things by:
+//! - **Latest-by-topic**: keyed by topic, only the most recent command for that topic is kept.
+//!   Correct for `state`/`connection`, which are VDA5050 last-value-wins topics where replaying a
+//!   backlog would be pointless - only the final value before reconnect matters.
+//! - **Ring-by-topic**: a bounded, in-order history per topic. Used for `visualization`, which is
+//!   a frame stream rather than a single current value, so replaying the buffered window (instead
+//!   of collapsing it to one frame) lets a subscriber see the motion that happened during the
+//!   outage rather than a jump cut.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::config::OutboundQueueConfig;
+use crate::mqtt_dispatcher::PublishCommand;
+
+/// What to do with a ring-buffered lane once it's full and a new sample arrives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// Discard the oldest buffered sample to make room for the new one.
+    DropOldest,
+    /// Discard every sample buffered so far and keep just the new one.
+    KeepLatestOnly,
+}
+
+impl DropPolicy {
+    /// Parses `OutboundQueueConfig::drop_policy`, defaulting to `DropOldest` for anything other
+    /// than an exact `"keep_latest_only"` match, so an unset/typo'd config value doesn't silently
+    /// discard more history than the user asked for.
+    fn from_config_str(value: &str) -> Self {
+        match value {
+            "keep_latest_only" => DropPolicy::KeepLatestOnly,
+            _ => DropPolicy::DropOldest,
+        }
+    }
+}
+
+/// Suffix that routes a [`PublishCommand`] to the ring-buffered lane instead of the
+/// latest-by-topic lane.
+const RING_BUFFERED_TOPIC_SUFFIX: &str = "/visualization";
+
+/// Per-vehicle (or, under `MqttDispatcher`, per-connection) outbound buffer for messages that
+/// couldn't be published while disconnected.
+pub struct OutboundQueue {
+    latest_by_topic: HashMap<String, PublishCommand>,
+    ring_by_topic: HashMap<String, VecDeque<PublishCommand>>,
+    ring_depth: usize,
+    drop_policy: DropPolicy,
+}
+
+impl OutboundQueue {
+    pub fn new(config: &OutboundQueueConfig) -> Self {
+        Self {
+            latest_by_topic: HashMap::new(),
+            ring_by_topic: HashMap::new(),
+            ring_depth: config.visualization_buffer_depth,
+            drop_policy: DropPolicy::from_config_str(&config.drop_policy),
+        }
+    }
+
+    /// Buffers `command` instead of publishing it. Visualization topics go to the bounded ring
+    /// lane; everything else (state, connection, acks) goes to the latest-by-topic lane.
+    pub fn buffer(&mut self, command: PublishCommand) {
+        if command.topic.ends_with(RING_BUFFERED_TOPIC_SUFFIX) {
+            self.buffer_ring(command);
+        } else {
+            self.latest_by_topic.insert(command.topic.clone(), command);
+        }
+    }
+
+    fn buffer_ring(&mut self, command: PublishCommand) {
+        let ring = self.ring_by_topic.entry(command.topic.clone()).or_default();
+
+        if ring.len() >= self.ring_depth.max(1) {
+            match self.drop_policy {
+                DropPolicy::DropOldest => {
+                    ring.pop_front();
+                }
+                DropPolicy::KeepLatestOnly => {
+                    ring.clear();
+                }
+            }
+        }
+        ring.push_back(command);
+    }
+
+    /// Drains and returns every buffered command, latest state/connection values first and then
+    /// each topic's visualization history in order, leaving the queue empty. Meant to be called
+    /// once on reconnect, before replaying anything else, so the backlog accumulated during the
+    /// outage goes out before the fresh resync.
+    pub fn drain(&mut self) -> Vec<PublishCommand> {
+        let mut drained: Vec<PublishCommand> = self.latest_by_topic.drain().map(|(_, command)| command).collect();
+        for (_, ring) in self.ring_by_topic.drain() {
+            drained.extend(ring);
+        }
+        drained
+    }
+
+    /// Whether anything is currently buffered.
+    pub fn is_empty(&self) -> bool {
+        self.latest_by_topic.is_empty() && self.ring_by_topic.values().all(VecDeque::is_empty)
+    }
+}
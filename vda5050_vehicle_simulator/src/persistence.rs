@@ -0,0 +1,80 @@
+//! Optional on-disk persistence of a [`VehicleSimulator`](crate::vehicle_simulator::VehicleSimulator)'s `State` across restarts, so a
+//! simulated AGV resumes its pose, order identifiers, and in-progress node/edge/action states
+//! after a crash or restart instead of appearing as a brand-new, uninitialized vehicle to the
+//! fleet manager - analogous to how a node persists and reloads its peer/cluster list on boot
+//! instead of bootstrapping from scratch.
+//!
+//! Only the fields needed to resume reporting accurate progress are persisted (position, order
+//! identifiers, node/edge/action states, battery); the active `Order` itself and the
+//! `action_registry` blocking-type metadata derived from it are not, so a restarted vehicle still
+//! needs master control to resend the remaining order as an update before it resumes driving.
+
+use std::fs;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::fleet_simulator::SharedFleet;
+use crate::protocol::vda5050_common::AgvPosition;
+use crate::protocol::vda_2_0_0::vda5050_2_0_0_state::{ActionState, BatteryState, EdgeState, NodeState};
+
+/// The subset of `State` worth carrying across a restart.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedState {
+    pub agv_position: AgvPosition,
+    pub order_id: String,
+    pub order_update_id: u32,
+    pub last_node_id: String,
+    pub last_node_sequence_id: u32,
+    pub node_states: Vec<NodeState>,
+    pub edge_states: Vec<EdgeState>,
+    pub action_states: Vec<ActionState>,
+    pub battery_state: BatteryState,
+}
+
+/// Load a previously-saved snapshot from `path`, or `None` if no file is there yet or it fails
+/// to parse, in which case the caller falls back to bootstrapping a brand-new vehicle.
+pub fn load_snapshot(path: &str) -> Option<PersistedState> {
+    let contents = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            println!("Error parsing persisted state at {}: {:?}", path, e);
+            None
+        }
+    }
+}
+
+/// Write `snapshot` to `path`, overwriting whatever was there. Errors are logged rather than
+/// propagated, matching how the rest of the simulator treats best-effort I/O.
+fn save_snapshot(path: &str, snapshot: &PersistedState) {
+    let serialized = match serde_json::to_string(snapshot) {
+        Ok(serialized) => serialized,
+        Err(e) => {
+            println!("Error serializing vehicle state for {}: {:?}", path, e);
+            return;
+        }
+    };
+
+    if let Err(e) = fs::write(path, serialized) {
+        println!("Error persisting vehicle state to {}: {:?}", path, e);
+    }
+}
+
+/// Save the vehicle at `vehicle_index` within `fleet`'s current [`PersistedState`] to `path`
+/// right now, e.g. on clean shutdown.
+pub async fn persist_now(path: &str, fleet: &SharedFleet, vehicle_index: usize) {
+    let snapshot = fleet.lock().await.vehicles()[vehicle_index].persisted_state();
+    save_snapshot(path, &snapshot);
+}
+
+/// Save the vehicle at `vehicle_index` within `fleet`'s [`PersistedState`] to `path` every
+/// `interval` until the process exits. Meant to be spawned as its own task alongside a vehicle's
+/// MQTT dispatch.
+pub async fn persist_periodically(path: String, interval: Duration, fleet: SharedFleet, vehicle_index: usize) {
+    loop {
+        tokio::time::sleep(interval).await;
+        persist_now(&path, &fleet, vehicle_index).await;
+    }
+}
@@ -0,0 +1,7 @@
+//! VDA5050 wire types, grouped by protocol version.
+
+pub mod vda5050_common;
+pub mod vda_1_1_0;
+pub mod vda_2_0_0;
+pub mod vda_2_1_0;
+pub mod version;
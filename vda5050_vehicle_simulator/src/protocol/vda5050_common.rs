@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+/// header_id of a message. The header_id is defined per topic and incremented by 1 with each sent (but not necessarily received) message.
+pub type HeaderId = u32;
+
+/// Node position. Optional for vehicle types that do not require the node position (e.g. line-guided vehicles).
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NodePosition {
+    /// X position on the map in reference to the map coordinate system. Precision is up to the specific implementation.
+    pub x: f32,
+    /// Y position on the map in reference to the map coordinate system. Precision is up to the specific implementation.
+    pub y: f32,
+    /// Absolute orientation of the AGV on the node. Optional: vehicle can plan the path by itself.
+    pub theta: Option<f32>,
+    /// Allowed deviation radius in meters. If the AGV passes a node within this distance, the node is considered to have been visited.
+    pub allowed_deviation_xy: Option<f32>,
+    /// Allowed deviation from theta in radians.
+    pub allowed_deviation_theta: Option<f32>,
+    /// Unique identification of the map in which the position is referenced. Each map has the same origin of coordinates.
+    pub map_id: String,
+    /// Additional information on the map.
+    pub map_description: Option<String>,
+}
+
+/// Current position of the AGV on the map.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AgvPosition {
+    /// X position on the map in reference to the map coordinate system.
+    pub x: f32,
+    /// Y position on the map in reference to the map coordinate system.
+    pub y: f32,
+    /// True if the AGV's position is initialized, false if position is not initialized yet.
+    pub position_initialized: bool,
+    /// Orientation of the AGV in radians.
+    pub theta: f32,
+    /// Unique identification of the map in which the position is referenced.
+    pub map_id: String,
+    /// Value for the deviation range of the position in meters, used e.g. for safety applications.
+    pub deviation_range: Option<f32>,
+    /// Additional information on the map.
+    pub map_description: Option<String>,
+    /// Describes the quality of the localization, with 0.0 meaning no localization and 1.0 meaning perfect localization.
+    pub localization_score: Option<f32>,
+}
+
+/// Weighted control point of a rational B-spline curve.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlPoint {
+    /// X coordinate described in the world coordinate system.
+    pub x: f32,
+    /// Y coordinate described in the world coordinate system.
+    pub y: f32,
+    /// Weight of the control point, which is different to the trajectory's curve/weight. Default value if not provided: 1.0.
+    pub weight: Option<f32>,
+    /// Orientation of the AGV at this control point, if explicitly defined by the trajectory.
+    pub orientation: Option<f32>,
+}
+
+/// Trajectory segment, defined as a NURBS (Non-Uniform Rational Basis Spline). Defines the curve on which the AGV should move between two nodes.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Trajectory {
+    /// Degree of the NURBS curve (>= 1).
+    pub degree: i64,
+    /// Sequence of parameter values that determine where and how the control points affect the curve. Must be non-decreasing, with `knot_vector.len() == control_points.len() + degree + 1`.
+    pub knot_vector: Vec<f32>,
+    /// List of control points shaping the curve, each carrying an optional weight.
+    pub control_points: Vec<ControlPoint>,
+}
+
+/// Current velocity of the AGV in vehicle coordinates.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Velocity {
+    /// The AGV's velocity in its x direction.
+    pub vx: Option<f32>,
+    /// The AGV's velocity in its y direction.
+    pub vy: Option<f32>,
+    /// The AGV's turning speed around its z axis.
+    pub omega: Option<f32>,
+}
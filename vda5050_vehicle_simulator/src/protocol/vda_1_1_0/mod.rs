@@ -0,0 +1,8 @@
+//! VDA5050 protocol version 1.1.0 message types.
+//!
+//! 1.1.0 predates [`vda_2_0_0`](crate::protocol::vda_2_0_0) and is missing a handful of fields
+//! 2.0.0 later added (e.g. `zoneSetId`), but every field 1.1.0 does have keeps the same meaning
+//! in 2.0.0, and the fields it lacks are already `Option`s on the 2.0.0 types. So, like
+//! [`vda_2_1_0`](crate::protocol::vda_2_1_0), there is no dedicated 1.1.0 struct here: a 1.1.0
+//! message deserializes directly as its `vda_2_0_0` equivalent via
+//! [`crate::protocol::version`], with the fields it never had simply defaulting to `None`.
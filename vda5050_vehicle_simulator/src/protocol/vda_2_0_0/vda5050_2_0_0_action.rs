@@ -18,7 +18,7 @@ pub struct Action {
 }
 
 /// Regulates if the action is allowed to be executed during movement and/or parallel to other actions.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum BlockingType {
     /// Action can happen in parallel with others, including movement.
@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::vda5050_common::{AgvPosition, HeaderId, NodePosition, Trajectory, Velocity};
+
+/// Current state of the AGV, published periodically and after every event that changes it.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct State {
+    /// header_id of the message. The header_id is defined per topic and incremented by 1 with each sent (but not necessarily received) message.
+    pub header_id: HeaderId,
+    /// Timestamp (ISO8601, UTC); YYYY-MM-DDTHH:mm:ss.ssZ; e.g. 2017-04-15T11:40:03.12Z
+    pub timestamp: String,
+    /// Version of the protocol [Major].[Minor].[Patch], e.g. 1.3.2
+    pub version: String,
+    /// Manufacturer of the AGV
+    pub manufacturer: String,
+    /// Serial number of the AGV
+    pub serial_number: String,
+    /// True: AGV is driving and/or rotating. Other movements of the AGV (e.g. lift) are not included.
+    pub driving: bool,
+    /// Distance since the last node was passed, in meters.
+    pub distance_since_last_node: Option<f32>,
+    /// Current operating mode of the AGV.
+    pub operating_mode: OperatingMode,
+    /// Array of node states, the first entry being the next node to be traversed/reached.
+    pub node_states: Vec<NodeState>,
+    /// Array of edge states, the first entry being the edge currently being traversed or about to be.
+    pub edge_states: Vec<EdgeState>,
+    /// node_id of last reached node, or empty string if no node has been reached yet.
+    pub last_node_id: String,
+    /// Unique order identification of the current order or the previous finished order.
+    pub order_id: String,
+    /// order_update_id of the current order or the previous finished order.
+    pub order_update_id: u32,
+    /// sequence_id of the last reached node.
+    pub last_node_sequence_id: u32,
+    /// Array of action states, describing the current status of every action known to the AGV.
+    pub action_states: Vec<ActionState>,
+    /// Array of free-text information messages about the AGV, e.g. for debugging.
+    pub information: Vec<InfoEntry>,
+    /// Array of loads the AGV currently carries, if the AGV has load handling capabilities.
+    pub loads: Vec<Load>,
+    /// Array of error objects describing all active errors on the AGV.
+    pub errors: Vec<ErrorEntry>,
+    /// Current battery state of the AGV.
+    pub battery_state: BatteryState,
+    /// Safety-relevant state of the AGV, e.g. e-stop.
+    pub safety_state: SafetyState,
+    /// True: AGV is currently in a paused state, either because of a pause instant action or external trigger.
+    pub paused: Option<bool>,
+    /// True: AGV contains new base data for an already active order that master control should acknowledge.
+    pub new_base_request: Option<bool>,
+    /// Current position of the AGV on the map.
+    pub agv_position: Option<AgvPosition>,
+    /// Current velocity of the AGV.
+    pub velocity: Option<Velocity>,
+    /// Unique identification of the zone set that the AGV is currently using for path planning.
+    pub zone_set_id: Option<String>,
+}
+
+/// Current operating mode of the AGV.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OperatingMode {
+    /// Full automatic mode, orders are accepted and processed.
+    Automatic,
+    /// Driving is manual, but actions/order information is still reported automatically.
+    Semiautomatic,
+    /// AGV is being manually controlled; orders are rejected.
+    Manual,
+    /// AGV is under service/maintenance; orders are rejected.
+    Service,
+    /// AGV is being teleoperated.
+    Teleoperation,
+}
+
+/// Current state of a node the AGV still has to traverse or has already traversed as part of the active order.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeState {
+    /// Unique node identification.
+    pub node_id: String,
+    /// sequence_id of the node, as sent in the order.
+    pub sequence_id: u32,
+    /// Additional information on the node.
+    pub node_description: Option<String>,
+    /// True: node is part of the base and the AGV is allowed to drive to it.
+    pub released: bool,
+    /// Position of the node on the map, as sent in the order.
+    pub node_position: Option<NodePosition>,
+}
+
+/// Current state of an edge the AGV still has to traverse or is currently traversing as part of the active order.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeState {
+    /// Unique edge identification.
+    pub edge_id: String,
+    /// sequence_id of the edge, as sent in the order.
+    pub sequence_id: u32,
+    /// Additional information on the edge.
+    pub edge_description: Option<String>,
+    /// True: edge is part of the base and the AGV is allowed to traverse it.
+    pub released: bool,
+    /// Trajectory for this edge as sent in the order, used to show the AGV's planned path.
+    pub trajectory: Option<Trajectory>,
+}
+
+/// Current status of an action known to the AGV, whether part of an order or an instant action.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionState {
+    /// Unique action id, as set by master control in the order or instant action.
+    pub action_id: String,
+    /// action_type of the action, echoed back for convenience.
+    pub action_type: Option<String>,
+    /// Additional information on the action.
+    pub action_description: Option<String>,
+    /// Current status of the action.
+    pub action_status: ActionStatus,
+    /// Description of the result, e.g. the result of a load measurement.
+    pub result_description: Option<String>,
+}
+
+/// Status of an action over its lifetime.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActionStatus {
+    /// Action was received by the AGV but the node/edge the action is on has not been reached yet or is not yet released.
+    Waiting,
+    /// Action is being prepared before it is actually running, e.g. loading a gripper.
+    Initializing,
+    /// Action is running.
+    Running,
+    /// Action was running and is currently paused.
+    Paused,
+    /// Action was finished successfully.
+    Finished,
+    /// Action could not be finished and will not be retried.
+    Failed,
+}
+
+/// A free-text information message about the AGV, e.g. for debugging.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoEntry {
+    /// Type of the information, e.g. "debug" or "noticeable deviation".
+    pub info_type: String,
+    /// Severity level of the information.
+    pub info_level: InfoLevel,
+    /// Free-text description.
+    pub info_description: Option<String>,
+}
+
+/// Severity level of an information message.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum InfoLevel {
+    /// Used for visualization/debugging information.
+    Debug,
+    /// Used for information that should be visible to an operator.
+    Info,
+}
+
+/// A load that the AGV currently carries, if the AGV has load handling capabilities.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Load {
+    /// Unique load identification, e.g. a barcode or RFID.
+    pub load_id: Option<String>,
+    /// Type of the load, e.g. "EPAL", "container".
+    pub load_type: Option<String>,
+    /// Indicates which load handling/carrying unit of the AGV is used for this load.
+    pub load_position: Option<String>,
+}
+
+/// An error currently active on the AGV.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorEntry {
+    /// Type/name of the error.
+    pub error_type: String,
+    /// Severity level of the error.
+    pub error_level: ErrorLevel,
+    /// Free-text description of the error.
+    pub error_description: Option<String>,
+}
+
+/// Severity level of an error.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ErrorLevel {
+    /// AGV is ready to continue driving.
+    Warning,
+    /// AGV is not able to continue driving without manual intervention.
+    Fatal,
+}
+
+/// Current battery state of the AGV.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BatteryState {
+    /// State of charge in percent.
+    pub battery_charge: f32,
+    /// Battery voltage in volts.
+    pub battery_voltage: Option<f32>,
+    /// State of health in percent.
+    pub battery_health: Option<f32>,
+    /// True: charging in progress.
+    pub charging: bool,
+    /// Estimated remaining reach based on current battery state, in meters.
+    pub reach: Option<f32>,
+}
+
+/// Safety-relevant state of the AGV.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SafetyState {
+    /// Acknowledge-type of the e-stop.
+    pub e_stop: EStop,
+    /// True: field violation on one of the safety fields.
+    pub field_violation: bool,
+}
+
+/// Acknowledge-type of an e-stop.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum EStop {
+    /// No e-stop active.
+    None,
+    /// Auto-acknowledgeable e-stop is activated, e.g. by a safety field.
+    Autoack,
+    /// E-stop has to be acknowledged manually at the AGV.
+    Manual,
+    /// E-stop has to be acknowledged remotely.
+    Remote,
+}
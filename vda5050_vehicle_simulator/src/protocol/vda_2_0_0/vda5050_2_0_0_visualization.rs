@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::vda5050_common::{AgvPosition, HeaderId, Velocity};
+
+/// Visualization data, intended purely for visualization purposes and not safety-relevant. Can be published at a higher frequency than `state`.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Visualization {
+    /// header_id of the message. The header_id is defined per topic and incremented by 1 with each sent (but not necessarily received) message.
+    pub header_id: HeaderId,
+    /// Timestamp (ISO8601, UTC); YYYY-MM-DDTHH:mm:ss.ssZ; e.g. 2017-04-15T11:40:03.12Z
+    pub timestamp: String,
+    /// Version of the protocol [Major].[Minor].[Patch], e.g. 1.3.2
+    pub version: String,
+    /// Manufacturer of the AGV
+    pub manufacturer: String,
+    /// Serial number of the AGV
+    pub serial_number: String,
+    /// Current position of the AGV on the map.
+    pub agv_position: Option<AgvPosition>,
+    /// Current velocity of the AGV.
+    pub velocity: Option<Velocity>,
+}
@@ -0,0 +1,8 @@
+//! VDA5050 protocol version 2.1.0 message types.
+//!
+//! 2.1.0 is a minor, backward-compatible revision of [`vda_2_0_0`](crate::protocol::vda_2_0_0):
+//! every 2.0.0 field keeps its meaning, and the fields 2.1.0 introduces are additive. Only the
+//! types that actually gained fields get a 2.1.0-specific definition here; the rest are reused
+//! as-is from `vda_2_0_0` via [`crate::protocol::version`]'s downcast.
+
+pub mod vda5050_2_1_0_order;
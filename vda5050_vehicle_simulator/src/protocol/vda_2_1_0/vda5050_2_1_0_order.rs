@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::vda5050_common::{HeaderId, NodePosition, Trajectory};
+use crate::protocol::vda_2_0_0::vda5050_2_0_0_action::Action;
+use crate::protocol::vda_2_0_0::vda5050_2_0_0_order as vda_2_0_0;
+
+/// An order to be processed by the AGV, made up of a sequence of nodes connected by edges.
+///
+/// Identical to [`vda_2_0_0`'s `Order`](crate::protocol::vda_2_0_0::vda5050_2_0_0_order::Order);
+/// only [`Node`] gained a field in 2.1.0.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    /// header_id of the message. The header_id is defined per topic and incremented by 1 with each sent (but not necessarily received) message.
+    pub header_id: HeaderId,
+    /// Timestamp (ISO8601, UTC); YYYY-MM-DDTHH:mm:ss.ssZ; e.g. 2017-04-15T11:40:03.12Z
+    pub timestamp: String,
+    /// Version of the protocol [Major].[Minor].[Patch], e.g. 1.3.2
+    pub version: String,
+    /// Manufacturer of the AGV
+    pub manufacturer: String,
+    /// Serial number of the AGV
+    pub serial_number: String,
+    /// Order identification. This is to be used to identify multiple order messages that belong to the same order.
+    pub order_id: String,
+    /// order_update_id to identify the sequence of an order update. Is reset to 0 whenever order_id changes.
+    pub order_update_id: u32,
+    /// Unique identifier of the zone set that the AGV has to use for navigation or that was used by master control for planning.
+    pub zone_set_id: Option<String>,
+    /// Array of nodes to be traversed for fulfilling the order, in sequential order.
+    pub nodes: Vec<Node>,
+    /// Array of edges connecting the nodes, in sequential order.
+    pub edges: Vec<Edge>,
+}
+
+/// A node the AGV either passes through or stops at, optionally triggering actions.
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Node {
+    /// Unique node identification.
+    pub node_id: String,
+    /// Sequence id to enforce ordering; each order must have unique, ascending sequence ids (node and edge interleaved).
+    pub sequence_id: u32,
+    /// Additional information on the node.
+    pub node_description: Option<String>,
+    /// True indicates that the node is part of the base (the AGV is allowed to drive to it). False indicates it is part of the horizon (preview-only).
+    pub released: bool,
+    /// Defines the position on the map in world coordinates. Optional for vehicles that do not require node positions (e.g. line-guided).
+    pub node_position: Option<NodePosition>,
+    /// Array of actions to be executed on the node.
+    pub actions: Vec<Action>,
+    /// 2.1.0 addition: node_id of another node this one has been merged with by master control
+    /// for fleet coordination. Unknown to 2.0.0 simulators, which downcast it away.
+    pub merge_node_id: Option<String>,
+}
+
+/// An edge describes the connection between two nodes that the AGV traverses.
+///
+/// Identical to [`vda_2_0_0`'s `Edge`](crate::protocol::vda_2_0_0::vda5050_2_0_0_order::Edge).
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Edge {
+    /// Unique edge identification.
+    pub edge_id: String,
+    /// Sequence id to enforce ordering; each order must have unique, ascending sequence ids (node and edge interleaved).
+    pub sequence_id: u32,
+    /// Additional information on the edge.
+    pub edge_description: Option<String>,
+    /// True indicates that the edge is part of the base. False indicates it is part of the horizon.
+    pub released: bool,
+    /// node_id of the start node.
+    pub start_node_id: String,
+    /// node_id of the end node.
+    pub end_node_id: String,
+    /// Permitted maximum speed on the edge in m/s.
+    pub max_speed: Option<f32>,
+    /// Maximum allowed height of the vehicle, including the load, on the edge in meters.
+    pub max_height: Option<f32>,
+    /// Minimum allowed height of the load handling device on the edge in meters.
+    pub min_height: Option<f32>,
+    /// Orientation of the AGV on the edge, in radians.
+    pub orientation: Option<f32>,
+    /// Sets the type of orientation, e.g. "GLOBAL" or "TANGENTIAL".
+    pub orientation_type: Option<String>,
+    /// Sets direction at junctions for line-guided or wire-guided vehicles.
+    pub direction: Option<String>,
+    /// True: rotation is allowed on the edge. False: rotation is not allowed.
+    pub rotation_allowed: Option<bool>,
+    /// Maximum rotation speed in rad/s.
+    pub max_rotation_speed: Option<f32>,
+    /// Length of the edge in meters.
+    pub length: Option<f32>,
+    /// Trajectory for this edge as a NURBS. Defines an alternative to the direct path between start and end node.
+    pub trajectory: Option<Trajectory>,
+    /// Array of actions to be executed on the edge.
+    pub actions: Vec<Action>,
+}
+
+/// Normalizes a 2.1.0 [`Order`] into the simulator's shared `vda_2_0_0::Order` representation,
+/// dropping [`Node::merge_node_id`], the one field 2.0.0 doesn't know about.
+impl From<Order> for vda_2_0_0::Order {
+    fn from(order: Order) -> Self {
+        vda_2_0_0::Order {
+            header_id: order.header_id,
+            timestamp: order.timestamp,
+            version: order.version,
+            manufacturer: order.manufacturer,
+            serial_number: order.serial_number,
+            order_id: order.order_id,
+            order_update_id: order.order_update_id,
+            zone_set_id: order.zone_set_id,
+            nodes: order.nodes.into_iter().map(vda_2_0_0::Node::from).collect(),
+            edges: order.edges.into_iter().map(vda_2_0_0::Edge::from).collect(),
+        }
+    }
+}
+
+impl From<Node> for vda_2_0_0::Node {
+    fn from(node: Node) -> Self {
+        vda_2_0_0::Node {
+            node_id: node.node_id,
+            sequence_id: node.sequence_id,
+            node_description: node.node_description,
+            released: node.released,
+            node_position: node.node_position,
+            actions: node.actions,
+        }
+    }
+}
+
+impl From<Edge> for vda_2_0_0::Edge {
+    fn from(edge: Edge) -> Self {
+        vda_2_0_0::Edge {
+            edge_id: edge.edge_id,
+            sequence_id: edge.sequence_id,
+            edge_description: edge.edge_description,
+            released: edge.released,
+            start_node_id: edge.start_node_id,
+            end_node_id: edge.end_node_id,
+            max_speed: edge.max_speed,
+            max_height: edge.max_height,
+            min_height: edge.min_height,
+            orientation: edge.orientation,
+            orientation_type: edge.orientation_type,
+            direction: edge.direction,
+            rotation_allowed: edge.rotation_allowed,
+            max_rotation_speed: edge.max_rotation_speed,
+            length: edge.length,
+            trajectory: edge.trajectory,
+            actions: edge.actions,
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Version negotiation between the simulator's configured VDA5050 version
+//! (`VehicleConfig::vda_full_version`) and the `version` field on incoming wire messages.
+//!
+//! [`ProtocolVersion`] is the common API every version module sits behind, the way `v4`/`v5`
+//! submodules sit behind a shared client API in MQTT crates. The simulator's state machine only
+//! understands [`vda_2_0_0`](crate::protocol::vda_2_0_0) types, so [`decode_order`] and
+//! [`decode_instant_actions`] dispatch on the incoming message's `ProtocolVersion` and normalize
+//! every version into that shared representation: [`vda_1_1_0`](crate::protocol::vda_1_1_0) and
+//! [`vda_2_1_0`](crate::protocol::vda_2_1_0) differ from `2.0.0` only in fields that are
+//! optional either way, so deserializing them directly as the `vda_2_0_0` type already performs
+//! the normalization (dropping fields `2.0.0` doesn't know, defaulting ones it has that the
+//! sender didn't send). A message whose major version the simulator doesn't support at all, or
+//! whose major version doesn't match the simulator's configured major version, is rejected.
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::protocol::vda_2_0_0::vda5050_2_0_0_instant_actions::InstantActions;
+use crate::protocol::vda_2_0_0::vda5050_2_0_0_order::Order;
+use crate::protocol::vda_2_1_0::vda5050_2_1_0_order::Order as OrderV2_1_0;
+
+/// VDA5050 major.minor protocol versions this simulator knows how to decode, parsed from a
+/// `[Major].[Minor].[Patch]` version string's major and minor components. Every variant normalizes into the
+/// same [`vda_2_0_0`](crate::protocol::vda_2_0_0) wire types once decoded; see the version
+/// submodules for what, if anything, differs on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum ProtocolVersion {
+    V1_1_0,
+    V2_0_0,
+    V2_1_0,
+}
+
+impl ProtocolVersion {
+    /// Parses a `[Major].[Minor].[Patch]` version string into the `ProtocolVersion` it reports.
+    fn from_version_str(version: &str) -> Option<Self> {
+        let mut parts = version.split('.');
+        let major: u32 = parts.next()?.parse().ok()?;
+        let minor: u32 = parts.next().and_then(|m| m.parse().ok()).unwrap_or(0);
+
+        match (major, minor) {
+            (1, _) => Some(ProtocolVersion::V1_1_0),
+            (2, minor) if minor >= 1 => Some(ProtocolVersion::V2_1_0),
+            (2, _) => Some(ProtocolVersion::V2_0_0),
+            _ => None,
+        }
+    }
+
+    fn major(self) -> u32 {
+        match self {
+            ProtocolVersion::V1_1_0 => 1,
+            ProtocolVersion::V2_0_0 | ProtocolVersion::V2_1_0 => 2,
+        }
+    }
+}
+
+/// Error returned when an incoming message's protocol version is incompatible with the
+/// simulator's configured version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolVersionError {
+    /// Major version the simulator is configured for.
+    pub configured_major: u32,
+    /// `version` field reported by the incoming message.
+    pub message_version: String,
+}
+
+impl fmt::Display for ProtocolVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "incompatible protocol version: simulator is configured for major version {}, but message reports version \"{}\"",
+            self.configured_major, self.message_version
+        )
+    }
+}
+
+impl std::error::Error for ProtocolVersionError {}
+
+/// Error decoding a versioned wire message: either its version was incompatible or unrecognized,
+/// or the payload itself wasn't valid JSON for the target type.
+#[derive(Debug)]
+pub enum DecodeError {
+    Version(ProtocolVersionError),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Version(e) => write!(f, "{}", e),
+            DecodeError::Json(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<ProtocolVersionError> for DecodeError {
+    fn from(e: ProtocolVersionError) -> Self {
+        DecodeError::Version(e)
+    }
+}
+
+impl From<serde_json::Error> for DecodeError {
+    fn from(e: serde_json::Error) -> Self {
+        DecodeError::Json(e)
+    }
+}
+
+/// Just enough of a message's envelope to read its `version` field before committing to
+/// deserializing the full, concrete type.
+#[derive(Deserialize)]
+struct VersionEnvelope {
+    version: String,
+}
+
+/// Parses the leading `major` component of a `[Major].[Minor].[Patch]` version string.
+fn major_version(version: &str) -> Option<u32> {
+    version.split('.').next()?.parse().ok()
+}
+
+/// Resolves `message_version` to a [`ProtocolVersion`] and checks its major component matches
+/// `configured_version`'s. An unrecognized major version, or one that doesn't match the
+/// simulator's configured major version, is rejected; minor/patch differences within the same
+/// major version are always accepted, since those are the differences the version submodules
+/// already normalize away.
+fn negotiate_version(
+    configured_version: &str,
+    message_version: &str,
+) -> Result<ProtocolVersion, ProtocolVersionError> {
+    let configured_major = major_version(configured_version).unwrap_or(0);
+    let resolved = ProtocolVersion::from_version_str(message_version);
+
+    match resolved {
+        Some(version) if version.major() == configured_major => Ok(version),
+        _ => Err(ProtocolVersionError {
+            configured_major,
+            message_version: message_version.to_string(),
+        }),
+    }
+}
+
+/// Deserializes `payload` as `T` after resolving its `version` field to a [`ProtocolVersion`]
+/// compatible with `configured_version`, returning the resolved version alongside it so a caller
+/// that has a dedicated type for some versions (e.g. [`decode_order`]) can dispatch on it.
+fn decode<T: DeserializeOwned>(
+    payload: &str,
+    configured_version: &str,
+) -> Result<(T, ProtocolVersion), DecodeError> {
+    let envelope: VersionEnvelope = serde_json::from_str(payload)?;
+    let version = negotiate_version(configured_version, &envelope.version)?;
+    Ok((serde_json::from_str(payload)?, version))
+}
+
+/// Decodes an incoming `order` message, dispatching on its resolved [`ProtocolVersion`] and
+/// normalizing it into the shared `vda_2_0_0::Order` representation the simulator consumes.
+/// `2.1.0` has its own dedicated type ([`OrderV2_1_0`](crate::protocol::vda_2_1_0::vda5050_2_1_0_order::Order))
+/// since its `Node` gained a field `vda_2_0_0` doesn't know, so that version is deserialized into
+/// it and explicitly converted down; `1.1.0` and `2.0.0` have no type-level differences from
+/// `vda_2_0_0` (see the version submodules' doc comments) and deserialize directly as it.
+pub fn decode_order(payload: &str, configured_version: &str) -> Result<Order, DecodeError> {
+    let envelope: VersionEnvelope = serde_json::from_str(payload)?;
+    let version = negotiate_version(configured_version, &envelope.version)?;
+
+    match version {
+        ProtocolVersion::V2_1_0 => Ok(serde_json::from_str::<OrderV2_1_0>(payload)?.into()),
+        ProtocolVersion::V1_1_0 | ProtocolVersion::V2_0_0 => Ok(serde_json::from_str(payload)?),
+    }
+}
+
+/// Decodes an incoming `instantActions` message. Unlike [`decode_order`], no version submodule
+/// defines a dedicated `InstantActions`/`Action` type for any supported version (see their doc
+/// comments), so there is nothing to dispatch on beyond the version compatibility check: every
+/// version deserializes directly as `vda_2_0_0::InstantActions`.
+pub fn decode_instant_actions(
+    payload: &str,
+    configured_version: &str,
+) -> Result<InstantActions, DecodeError> {
+    decode(payload, configured_version).map(|(instant_actions, _version)| instant_actions)
+}
@@ -0,0 +1,114 @@
+//! Opt-in execution tracing for [`VehicleSimulator`](crate::vehicle_simulator::VehicleSimulator):
+//! a structured, timestamped log of every action transition, node arrival, edge entry/exit,
+//! position delta, and order acceptance/rejection the simulation produces per tick.
+//!
+//! The trace is keyed by a monotonic tick counter rather than a wall-clock timestamp. The tick on
+//! which a `Running` action reaches `Finished` is still decided by wall-clock time elapsed against
+//! `Settings.action_time`, so two simulators fed the same config and the same sequence of incoming
+//! messages produce byte-for-byte identical traces only when `action_time` is `0.0`; with a
+//! non-zero `action_time`, the tick of each such transition depends on how fast ticks are run.
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::protocol::vda_2_0_0::vda5050_2_0_0_state::ActionStatus;
+
+/// One thing the simulation did during a tick, in the order it happened.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum TraceEvent {
+    /// An action's `ActionStatus` advanced from `from` to `to`.
+    ActionTransition {
+        action_id: String,
+        from: ActionStatus,
+        to: ActionStatus,
+    },
+    /// The vehicle arrived at `node_id`, which becomes its `last_node_id`.
+    NodeArrived { node_id: String },
+    /// The vehicle started driving `edge_id`.
+    EdgeEntered { edge_id: String },
+    /// The vehicle finished driving `edge_id` and arrived at its end node.
+    EdgeExited { edge_id: String },
+    /// The vehicle's position moved by `(dx, dy)` this tick.
+    PositionDelta { dx: f32, dy: f32 },
+    /// An incoming order was accepted.
+    OrderAccepted { order_id: String },
+    /// An incoming order was rejected, with the reason given to the caller.
+    OrderRejected { reason: String },
+}
+
+/// Something that wants to observe every [`TraceEvent`] a [`VehicleSimulator`](crate::vehicle_simulator::VehicleSimulator)
+/// produces, tagged with the monotonic tick it happened on. `Send` because `VehicleSimulator`
+/// (and therefore its tracer) is shared across the MQTT subscribe/publish tasks behind an
+/// `Arc<Mutex<_>>`.
+pub trait Tracer: Send {
+    fn trace(&mut self, tick: u64, event: TraceEvent);
+}
+
+/// Collects every traced event in memory, in order, for tests to assert on.
+#[derive(Default, Debug)]
+pub struct InMemoryTracer {
+    pub events: Vec<(u64, TraceEvent)>,
+}
+
+impl InMemoryTracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The `TraceEvent`s recorded, dropping their tick, for assertions that only care about
+    /// order.
+    pub fn events(&self) -> impl Iterator<Item = &TraceEvent> {
+        self.events.iter().map(|(_, event)| event)
+    }
+}
+
+impl Tracer for InMemoryTracer {
+    fn trace(&mut self, tick: u64, event: TraceEvent) {
+        self.events.push((tick, event));
+    }
+}
+
+/// Lets a `Tracer` be shared between a [`VehicleSimulator`](crate::vehicle_simulator::VehicleSimulator)
+/// (which owns a `Box<dyn Tracer>`) and whoever wants to inspect it afterwards, e.g. a test
+/// holding onto an `Arc<Mutex<InMemoryTracer>>`.
+impl<T: Tracer> Tracer for Arc<Mutex<T>> {
+    fn trace(&mut self, tick: u64, event: TraceEvent) {
+        self.lock().unwrap().trace(tick, event);
+    }
+}
+
+/// Streams every traced event to `writer` as one JSON object per line: `{"tick":..,"event":..}`.
+/// Lossy if a write fails partway through a session; errors are logged rather than propagated.
+pub struct JsonLinesTracer<W: Write + Send> {
+    writer: W,
+}
+
+impl<W: Write + Send> JsonLinesTracer<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write + Send> Tracer for JsonLinesTracer<W> {
+    fn trace(&mut self, tick: u64, event: TraceEvent) {
+        #[derive(Serialize)]
+        struct Line<'a> {
+            tick: u64,
+            #[serde(flatten)]
+            event: &'a TraceEvent,
+        }
+
+        let result: io::Result<()> = (|| {
+            serde_json::to_writer(&mut self.writer, &Line { tick, event: &event })
+                .map_err(io::Error::from)?;
+            self.writer.write_all(b"\n")
+        })();
+
+        if let Err(e) = result {
+            println!("Error writing trace event: {}", e);
+        }
+    }
+}
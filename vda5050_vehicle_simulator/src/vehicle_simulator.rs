@@ -1,18 +1,26 @@
 use chrono::{DateTime, Utc};
 use paho_mqtt as mqtt;
+use std::collections::HashMap;
 use std::time::Duration;
 use tokio::time::sleep;
 
+use crate::action_handlers::{self, ActionHandler};
+use crate::admin::{count_action_states_by_status, MetricsSnapshot};
 use crate::config;
+use crate::fleet_simulator::{ReservationOutcome, ReservationTable, TrafficStatus};
+use crate::mqtt_ack::{AckStatus, InstantActionsAck, ResponseTarget};
+use crate::mqtt_dispatcher::PublishCommand;
+use crate::persistence::{self, PersistedState};
 use crate::mqtt_utils;
 use crate::utils;
-use crate::protocol::vda_2_0_0::vda5050_2_0_0_action::{Action, ActionParameterValue};
+use crate::protocol::vda_2_0_0::vda5050_2_0_0_action::{Action, ActionParameterValue, BlockingType};
 use crate::protocol::vda_2_0_0::vda5050_2_0_0_connection::{Connection, ConnectionState};
 use crate::protocol::vda_2_0_0::vda5050_2_0_0_state::{State, ActionState, ActionStatus, NodeState, EdgeState, OperatingMode, BatteryState, SafetyState, EStop};
 use crate::protocol::vda_2_0_0::vda5050_2_0_0_visualization::Visualization;
-use crate::protocol::vda_2_0_0::vda5050_2_0_0_order::Order;
+use crate::protocol::vda_2_0_0::vda5050_2_0_0_order::{Order, Node, Edge};
 use crate::protocol::vda_2_0_0::vda5050_2_0_0_instant_actions::InstantActions;
-use crate::protocol::vda5050_common::{AgvPosition, NodePosition};
+use crate::protocol::vda5050_common::{AgvPosition, NodePosition, Velocity};
+use crate::tracer::{TraceEvent, Tracer};
 
 pub struct VehicleSimulator {
     connection_topic: String,
@@ -26,7 +34,41 @@ pub struct VehicleSimulator {
     instant_actions: Option<InstantActions>,
 
     config: config::Config,
-    action_start_time: Option<DateTime<Utc>>,
+    /// Definition (in particular `blocking_type`) of every action known to the AGV, keyed by
+    /// `action_id`, looked up while advancing [`ActionState`] transitions since the wire-level
+    /// `ActionState` itself doesn't carry blocking semantics.
+    action_registry: HashMap<String, Action>,
+    /// Handler executed when an action of a given `action_type` transitions to `Running`, keyed
+    /// by that `action_type`. Populated with [`action_handlers::default_handlers`] and
+    /// extensible via [`register_action_handler`](Self::register_action_handler).
+    action_handlers: HashMap<String, Box<dyn ActionHandler>>,
+    /// Wall-clock time each currently `Running` action entered that status, keyed by `action_id`,
+    /// used to know when `Settings.action_time` has elapsed and the action can be `Finished`.
+    action_timers: HashMap<String, DateTime<Utc>>,
+    /// Parameter `u` reached so far on the trajectory of the edge currently being traversed, if
+    /// any. Reset to `None` whenever the AGV starts driving a new edge.
+    trajectory_progress: Option<f32>,
+    /// Distance covered per tick right now, ramped each [`update_vehicle_position`](Self::update_vehicle_position)
+    /// call toward `Settings.speed` by [`ramp_velocity_toward`](Self::ramp_velocity_toward)
+    /// rather than jumping straight to it, so the vehicle accelerates/brakes instead of teleporting.
+    current_velocity: f32,
+    /// serial_number of the vehicle this one is currently yielding to under fleet traffic
+    /// management, if any. See [`FleetSimulator`](crate::fleet_simulator::FleetSimulator).
+    waiting_on: Option<String>,
+    /// edge_id of the edge the vehicle is currently driving, if any, so
+    /// [`update_vehicle_position`](Self::update_vehicle_position) can trace an `EdgeEntered`
+    /// exactly once per edge rather than once per tick.
+    current_edge_id: Option<String>,
+    /// Number of [`update_state`](Self::update_state) ticks elapsed, used as the deterministic
+    /// clock for [`Tracer`] events instead of a wall-clock timestamp.
+    tick: u64,
+    /// Opt-in sink for [`TraceEvent`]s. `None` unless [`set_tracer`](Self::set_tracer) was
+    /// called.
+    tracer: Option<Box<dyn Tracer>>,
+    /// Where to send a completion ack for an `instantActions` action_id, if the request that
+    /// introduced it carried MQTT5 `ResponseTopic`/`CorrelationData` properties. Entries are
+    /// removed as each completion ack is drained by [`drain_completion_acks`](Self::drain_completion_acks).
+    pending_acks: HashMap<String, ResponseTarget>,
 }
 
 impl VehicleSimulator {
@@ -42,8 +84,12 @@ impl VehicleSimulator {
         let state_topic = format!("{}/state", base_topic);
         let visualization_topic = format!("{}/visualization", base_topic);
 
+        let persisted_state = config.persistence.enabled
+            .then(|| persistence::load_snapshot(&config.persistence.path))
+            .flatten();
+
         let connection = Self::create_initial_connection(&config);
-        let (state, agv_position) = Self::create_initial_state(&config);
+        let (state, agv_position) = Self::create_initial_state(&config, persisted_state);
         let visualization = Self::create_initial_visualization(&config, &agv_position);
 
         Self {
@@ -55,11 +101,106 @@ impl VehicleSimulator {
             visualization,
             order: None,
             instant_actions: None,
-            action_start_time: None,
+            action_registry: HashMap::new(),
+            action_handlers: action_handlers::default_handlers()
+                .into_iter()
+                .map(|handler| (handler.action_type().to_string(), handler))
+                .collect(),
+            action_timers: HashMap::new(),
+            trajectory_progress: None,
+            current_velocity: 0.0,
+            waiting_on: None,
+            current_edge_id: None,
+            tick: 0,
+            tracer: None,
+            pending_acks: HashMap::new(),
             config,
         }
     }
 
+    /// Start recording every [`TraceEvent`] this simulator produces to `tracer`. Opt-in: a
+    /// simulator with no tracer set does all the same work with none of the bookkeeping.
+    pub fn set_tracer(&mut self, tracer: Box<dyn Tracer>) {
+        self.tracer = Some(tracer);
+    }
+
+    fn trace(&mut self, event: TraceEvent) {
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.trace(self.tick, event);
+        }
+    }
+
+    /// Register `handler` for `handler.action_type()`, overriding any handler (built-in or
+    /// previously registered) already keyed by that `action_type`.
+    pub fn register_action_handler(&mut self, handler: Box<dyn ActionHandler>) {
+        self.action_handlers.insert(handler.action_type().to_string(), handler);
+    }
+
+    /// Serial number this vehicle simulates, used as its identity for fleet traffic management.
+    pub fn serial_number(&self) -> &str {
+        &self.config.vehicle.serial_number
+    }
+
+    /// Full VDA5050 protocol version (`[Major].[Minor].[Patch]`) this vehicle is configured to
+    /// speak, used to negotiate the version of incoming `order`/`instantActions` messages.
+    pub fn vda_full_version(&self) -> &str {
+        &self.config.vehicle.vda_full_version
+    }
+
+    /// serial_number of the vehicle this one is currently yielding to under fleet traffic
+    /// management, or `None` if it is free to proceed.
+    pub fn waiting_on(&self) -> Option<&str> {
+        self.waiting_on.as_deref()
+    }
+
+    /// Point-in-time snapshot of this vehicle's health for the admin `/metrics` and `/state`
+    /// endpoints. See [`MetricsSnapshot`](crate::admin::MetricsSnapshot).
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        let agv_position = self.state.agv_position.as_ref();
+
+        MetricsSnapshot {
+            serial_number: self.config.vehicle.serial_number.clone(),
+            battery_charge: self.state.battery_state.battery_charge,
+            driving: self.state.driving,
+            order_id: self.state.order_id.clone(),
+            order_update_id: self.state.order_update_id,
+            pending_node_states: self.state.node_states.len(),
+            pending_edge_states: self.state.edge_states.len(),
+            action_states_by_status: count_action_states_by_status(&self.state.action_states),
+            connection_header_id: self.connection.header_id,
+            state_header_id: self.state.header_id,
+            visualization_header_id: self.visualization.header_id,
+            x: agv_position.map(|position| position.x).unwrap_or(0.0),
+            y: agv_position.map(|position| position.y).unwrap_or(0.0),
+            theta: agv_position.map(|position| position.theta).unwrap_or(0.0),
+        }
+    }
+
+    /// Snapshot of the fields worth carrying across a restart. See
+    /// [`PersistedState`](crate::persistence::PersistedState).
+    pub fn persisted_state(&self) -> PersistedState {
+        PersistedState {
+            agv_position: self.state.agv_position.clone().unwrap_or(AgvPosition {
+                x: 0.0,
+                y: 0.0,
+                position_initialized: false,
+                theta: 0.0,
+                map_id: self.config.settings.map_id.clone(),
+                deviation_range: None,
+                map_description: None,
+                localization_score: None,
+            }),
+            order_id: self.state.order_id.clone(),
+            order_update_id: self.state.order_update_id,
+            last_node_id: self.state.last_node_id.clone(),
+            last_node_sequence_id: self.state.last_node_sequence_id,
+            node_states: self.state.node_states.clone(),
+            edge_states: self.state.edge_states.clone(),
+            action_states: self.state.action_states.clone(),
+            battery_state: self.state.battery_state.clone(),
+        }
+    }
+
     fn create_initial_connection(config: &config::Config) -> Connection {
         Connection {
             header_id: 0,
@@ -71,20 +212,22 @@ impl VehicleSimulator {
         }
     }
 
-    fn create_initial_state(config: &config::Config) -> (State, AgvPosition) {
-        let random_x = rand::random::<f32>() * 5.0 - 2.5;
-        let random_y = rand::random::<f32>() * 5.0 - 2.5;
-        
-        let agv_position = AgvPosition {
-            x: random_x,
-            y: random_y,
+    /// Builds the initial `State`, rehydrated from `persisted_state` when one was loaded so the
+    /// vehicle resumes its pose, order identifiers, and node/edge/action states instead of
+    /// spawning at a random, uninitialized position. `persisted_state` doesn't cover the active
+    /// `Order` itself, so a vehicle restored mid-route still needs master control to resend the
+    /// remaining order as an update before [`update_state`](Self::update_state) drives it again.
+    fn create_initial_state(config: &config::Config, persisted_state: Option<PersistedState>) -> (State, AgvPosition) {
+        let agv_position = persisted_state.as_ref().map(|persisted| persisted.agv_position.clone()).unwrap_or_else(|| AgvPosition {
+            x: rand::random::<f32>() * 5.0 - 2.5,
+            y: rand::random::<f32>() * 5.0 - 2.5,
             position_initialized: false,
             theta: 0.0,
             map_id: config.settings.map_id.clone(),
             deviation_range: None,
             map_description: None,
             localization_score: None,
-        };
+        });
 
         let state = State {
             header_id: 0,
@@ -95,23 +238,23 @@ impl VehicleSimulator {
             driving: false,
             distance_since_last_node: None,
             operating_mode: OperatingMode::Automatic,
-            node_states: vec![],
-            edge_states: vec![],
-            last_node_id: String::from(""),
-            order_id: String::from(""),
-            order_update_id: 0,
-            last_node_sequence_id: 0,
-            action_states: vec![],
+            node_states: persisted_state.as_ref().map(|persisted| persisted.node_states.clone()).unwrap_or_default(),
+            edge_states: persisted_state.as_ref().map(|persisted| persisted.edge_states.clone()).unwrap_or_default(),
+            last_node_id: persisted_state.as_ref().map(|persisted| persisted.last_node_id.clone()).unwrap_or_default(),
+            order_id: persisted_state.as_ref().map(|persisted| persisted.order_id.clone()).unwrap_or_default(),
+            order_update_id: persisted_state.as_ref().map(|persisted| persisted.order_update_id).unwrap_or(0),
+            last_node_sequence_id: persisted_state.as_ref().map(|persisted| persisted.last_node_sequence_id).unwrap_or(0),
+            action_states: persisted_state.as_ref().map(|persisted| persisted.action_states.clone()).unwrap_or_default(),
             information: vec![],
             loads: vec![],
             errors: vec![],
-            battery_state: BatteryState {
+            battery_state: persisted_state.map(|persisted| persisted.battery_state).unwrap_or(BatteryState {
                 battery_charge: 100.0,
                 battery_voltage: None,
                 battery_health: None,
                 charging: false,
                 reach: None,
-            },
+            }),
             safety_state: SafetyState {
                 e_stop: EStop::None,
                 field_violation: false,
@@ -141,26 +284,213 @@ impl VehicleSimulator {
         }
     }
 
-    pub fn run_action(&mut self, action: Action) {
-        if let Some(action_state_index) = self.find_action_state_index(&action.action_id) {
-            self.state.action_states[action_state_index].action_status = 
-                ActionStatus::Running;
-            
-            match action.action_type.as_str() {
-                "initPosition" => self.handle_init_position_action(&action),
-                _ => println!("Unknown action type: {}", action.action_type),
+    /// Advance `action_id` by exactly one step of the VDA5050 action lifecycle
+    /// (`Waiting -> Initializing -> Running -> Finished`), honoring `Settings.action_time` and
+    /// the `blocking_type` of actions already `Initializing` or `Running`. A no-op if the action is already in
+    /// a terminal status (`Finished`/`Failed`) or currently `Paused`.
+    fn step_action(&mut self, action_id: &str) {
+        let Some(action_state_index) = self.find_action_state_index(action_id) else {
+            return;
+        };
+
+        let from_status = self.state.action_states[action_state_index].action_status;
+        match from_status {
+            ActionStatus::Waiting => {
+                let blocking_type = self
+                    .action_registry
+                    .get(action_id)
+                    .map(|action| action.blocking_type)
+                    .unwrap_or(BlockingType::None);
+
+                if !self.is_blocked_from_starting(&blocking_type) {
+                    self.state.action_states[action_state_index].action_status = ActionStatus::Initializing;
+                    self.trace_action_transition(action_id, from_status, ActionStatus::Initializing);
+                }
+            }
+            ActionStatus::Initializing => {
+                self.state.action_states[action_state_index].action_status = ActionStatus::Running;
+                self.action_timers.insert(action_id.to_string(), Utc::now());
+                self.trace_action_transition(action_id, from_status, ActionStatus::Running);
+
+                if let Some(action) = self.action_registry.get(action_id).cloned() {
+                    self.execute_action_effect(&action, action_state_index);
+                }
+            }
+            ActionStatus::Running => {
+                let action_time = self.config.settings.action_time;
+                let elapsed = self
+                    .action_timers
+                    .get(action_id)
+                    .map(|start| (Utc::now() - *start).num_milliseconds() as f32 / 1000.0)
+                    .unwrap_or(f32::MAX);
+
+                if elapsed >= action_time {
+                    self.state.action_states[action_state_index].action_status = ActionStatus::Finished;
+                    self.action_timers.remove(action_id);
+                    self.trace_action_transition(action_id, from_status, ActionStatus::Finished);
+                }
+            }
+            ActionStatus::Paused | ActionStatus::Finished | ActionStatus::Failed => {}
+        }
+    }
+
+    /// Records an `ActionTransition` trace event, unless `from == to` (a no-op transition isn't
+    /// worth a trace line).
+    fn trace_action_transition(&mut self, action_id: &str, from: ActionStatus, to: ActionStatus) {
+        if from != to {
+            self.trace(TraceEvent::ActionTransition {
+                action_id: action_id.to_string(),
+                from,
+                to,
+            });
+        }
+    }
+
+    /// Whether starting an action with `blocking_type` must wait: any `Hard`-blocking action
+    /// currently initializing or running forbids every other action from starting, and a
+    /// `Soft`/`Hard` action itself cannot start while another `Soft`/`Hard` action is already
+    /// initializing or running.
+    fn is_blocked_from_starting(&self, blocking_type: &BlockingType) -> bool {
+        let running_blocking_types = || {
+            self.state
+                .action_states
+                .iter()
+                .filter(|action_state| {
+                    matches!(action_state.action_status, ActionStatus::Initializing | ActionStatus::Running)
+                })
+                .filter_map(|action_state| self.action_registry.get(&action_state.action_id))
+                .map(|action| action.blocking_type)
+        };
+
+        if running_blocking_types().any(|running| running == BlockingType::Hard) {
+            return true;
+        }
+
+        match blocking_type {
+            BlockingType::None => false,
+            BlockingType::Soft | BlockingType::Hard => {
+                running_blocking_types().any(|running| matches!(running, BlockingType::Soft | BlockingType::Hard))
+            }
+        }
+    }
+
+    /// Whether a currently `Running` `Hard`-blocking action forbids vehicle motion right now.
+    fn is_motion_blocked(&self) -> bool {
+        self.state
+            .action_states
+            .iter()
+            .filter(|action_state| action_state.action_status == ActionStatus::Running)
+            .filter_map(|action_state| self.action_registry.get(&action_state.action_id))
+            .any(|action| action.blocking_type == BlockingType::Hard)
+    }
+
+    /// Dispatch `action` to the [`ActionHandler`] registered for its `action_type`, recording the
+    /// handler's returned description (if any) on the matching `ActionState`. Removes the
+    /// handler from the registry for the duration of the call and reinserts it afterward, since
+    /// the handler needs `&mut self` to apply its effect.
+    fn execute_action_effect(&mut self, action: &Action, action_state_index: usize) {
+        let Some(handler) = self.action_handlers.remove(&action.action_type) else {
+            println!("Unknown action type: {}", action.action_type);
+            return;
+        };
+
+        let result_description = handler.execute(self, action);
+        if let Some(description) = result_description {
+            self.state.action_states[action_state_index].result_description = Some(description);
+        }
+
+        self.action_handlers.insert(action.action_type.clone(), handler);
+    }
+
+    /// Halt the vehicle and move every other `Running` action to `Paused`.
+    pub(crate) fn handle_pause_action(&mut self, triggering_action_id: &str) {
+        println!("Pausing vehicle and all running actions");
+        self.state.paused = Some(true);
+
+        let paused_action_ids: Vec<String> = self
+            .state
+            .action_states
+            .iter()
+            .filter(|action_state| action_state.action_id != triggering_action_id && action_state.action_status == ActionStatus::Running)
+            .map(|action_state| action_state.action_id.clone())
+            .collect();
+
+        for action_state in &mut self.state.action_states {
+            if paused_action_ids.contains(&action_state.action_id) {
+                action_state.action_status = ActionStatus::Paused;
             }
+        }
 
-            self.state.action_states[action_state_index].action_status = 
-                ActionStatus::Finished;
+        for action_id in paused_action_ids {
+            self.trace_action_transition(&action_id, ActionStatus::Running, ActionStatus::Paused);
         }
     }
 
+    /// Resume vehicle motion and restart every `Paused` action's timer from `Running`.
+    pub(crate) fn handle_resume_action(&mut self, triggering_action_id: &str) {
+        println!("Resuming vehicle and all paused actions");
+        self.state.paused = Some(false);
+
+        let resumed_action_ids: Vec<String> = self
+            .state
+            .action_states
+            .iter()
+            .filter(|action_state| action_state.action_id != triggering_action_id && action_state.action_status == ActionStatus::Paused)
+            .map(|action_state| action_state.action_id.clone())
+            .collect();
+
+        for action_state in &mut self.state.action_states {
+            if resumed_action_ids.contains(&action_state.action_id) {
+                action_state.action_status = ActionStatus::Running;
+            }
+        }
+
+        let resumed_at = Utc::now();
+        for action_id in &resumed_action_ids {
+            self.trace_action_transition(action_id, ActionStatus::Paused, ActionStatus::Running);
+        }
+        for action_id in resumed_action_ids {
+            self.action_timers.insert(action_id, resumed_at);
+        }
+    }
+
+    /// Fail every in-progress order action and drop the order's remaining node/edge states.
+    pub(crate) fn handle_cancel_order_action(&mut self, triggering_action_id: &str) {
+        println!("Cancelling order: {}", self.state.order_id);
+
+        let failed_actions: Vec<(String, ActionStatus)> = self
+            .state
+            .action_states
+            .iter()
+            .filter(|action_state| {
+                action_state.action_id != triggering_action_id
+                    && !matches!(action_state.action_status, ActionStatus::Finished | ActionStatus::Failed)
+            })
+            .map(|action_state| (action_state.action_id.clone(), action_state.action_status))
+            .collect();
+
+        for action_state in &mut self.state.action_states {
+            if failed_actions.iter().any(|(action_id, _)| action_id == &action_state.action_id) {
+                action_state.action_status = ActionStatus::Failed;
+            }
+        }
+
+        for (action_id, from_status) in failed_actions {
+            self.trace_action_transition(&action_id, from_status, ActionStatus::Failed);
+        }
+
+        self.state.node_states.clear();
+        self.state.edge_states.clear();
+        self.order = None;
+        self.trajectory_progress = None;
+        self.current_edge_id = None;
+    }
+
     fn find_action_state_index(&self, action_id: &str) -> Option<usize> {
         self.state.action_states.iter().position(|x| x.action_id == action_id)
     }
 
-    fn handle_init_position_action(&mut self, action: &Action) {
+    pub(crate) fn handle_init_position_action(&mut self, action: &Action) {
         println!("Executing init position action");
         
         let init_params = self.extract_init_position_parameters(action);
@@ -213,12 +543,44 @@ impl VehicleSimulator {
         }
     }
 
+    /// Connection-broken payload to register as the MQTT Last-Will when connecting, so the
+    /// broker itself publishes a retained `CONNECTIONBROKEN` message on this vehicle's
+    /// `connection` topic if it drops off the broker ungracefully.
+    pub fn last_will(&self) -> mqtt::Message {
+        let mut broken_connection = self.connection.clone();
+        broken_connection.connection_state = ConnectionState::ConnectionBroken;
+        let payload = serde_json::to_vec(&broken_connection).unwrap();
+
+        mqtt::MessageBuilder::new()
+            .topic(&self.connection_topic)
+            .payload(payload)
+            .qos(mqtt::QOS_1)
+            .retained(true)
+            .finalize()
+    }
+
+    /// Builds the connection publish, retained at QoS 1, out of `self.connection` as it
+    /// currently stands. Pure (no IO) so it can be routed either straight to a client or handed
+    /// to `mqtt_dispatcher::MqttDispatcher` over a `PublishCommand` channel.
+    pub fn connection_command(&self) -> PublishCommand {
+        PublishCommand {
+            topic: self.connection_topic.clone(),
+            payload: serde_json::to_string(&self.connection).unwrap(),
+            qos: mqtt::QOS_1,
+            retain: true,
+            correlation_data: None,
+        }
+    }
+
+    /// Publishes `self.connection` as-is, retained at QoS 1, so the broker always has an
+    /// up-to-date last message on the `connection` topic for late subscribers.
+    async fn publish_connection_message(&mut self, mqtt_cli: &mqtt::AsyncClient) {
+        self.connection_command().publish_confirmed(mqtt_cli).await;
+    }
+
     pub async fn publish_connection(&mut self, mqtt_cli: &mqtt::AsyncClient) {
         // Publish initial connection broken state
-        let json_connection_broken = serde_json::to_string(&self.connection).unwrap();
-        mqtt_utils::mqtt_publish(mqtt_cli, &self.connection_topic, &json_connection_broken)
-            .await
-            .unwrap();
+        self.publish_connection_message(mqtt_cli).await;
 
         // Wait for connection message to be published
         sleep(Duration::from_millis(1000)).await;
@@ -227,31 +589,74 @@ impl VehicleSimulator {
         self.connection.header_id += 1;
         self.connection.timestamp = utils::get_timestamp();
         self.connection.connection_state = ConnectionState::Online;
-        
-        let json_connection_online = serde_json::to_string(&self.connection).unwrap();
-        mqtt_utils::mqtt_publish(mqtt_cli, &self.connection_topic, &json_connection_online)
-            .await
-            .unwrap();
+        self.publish_connection_message(mqtt_cli).await;
     }
 
-    pub async fn publish_visualization(&mut self, mqtt_cli: &mqtt::AsyncClient) {
+    /// Bumps the connection message to `Online`, without otherwise touching the broker — used by
+    /// both [`republish_online`](Self::republish_online) and the dispatcher-routed command
+    /// equivalent so the two stay in lockstep.
+    fn online_publish_command(&mut self) -> PublishCommand {
+        self.connection.header_id += 1;
+        self.connection.timestamp = utils::get_timestamp();
+        self.connection.connection_state = ConnectionState::Online;
+        self.connection_command()
+    }
+
+    /// Re-announces `Online` after a reconnect, without replaying the
+    /// `ConnectionBroken -> Online` bootstrap sequence [`publish_connection`](Self::publish_connection)
+    /// runs on startup, so a fleet manager that saw the LWT's retained `ConnectionBroken` message
+    /// learns the vehicle is back without an extra transient state in between.
+    pub async fn republish_online(&mut self, mqtt_cli: &mqtt::AsyncClient) {
+        self.online_publish_command().publish_confirmed(mqtt_cli).await;
+    }
+
+    /// Builds the `Online` re-announce command for a caller (e.g. the dispatcher) that routes
+    /// publishes over a `PublishCommand` channel rather than calling `mqtt_cli` directly.
+    pub fn online_command(&mut self) -> PublishCommand {
+        self.online_publish_command()
+    }
+
+    /// Builds the next visualization publish. Visualization updates are high-frequency and
+    /// loss-tolerant, so they're marked QoS 0 / not retained, to avoid backpressure on the
+    /// publishing path.
+    pub fn visualization_command(&mut self) -> PublishCommand {
         self.visualization.header_id += 1;
         self.visualization.timestamp = utils::get_timestamp();
-        
-        let json_visualization = serde_json::to_string(&self.visualization).unwrap();
-        mqtt_utils::mqtt_publish(mqtt_cli, &self.visualization_topic, &json_visualization)
-            .await
-            .unwrap();
+
+        PublishCommand {
+            topic: self.visualization_topic.clone(),
+            payload: serde_json::to_string(&self.visualization).unwrap(),
+            qos: mqtt::QOS_0,
+            retain: false,
+            correlation_data: None,
+        }
     }
 
-    pub async fn publish_state(&mut self, mqtt_cli: &mqtt::AsyncClient) {
+    /// Visualization updates are high-frequency and loss-tolerant, so they're fired and
+    /// forgotten at QoS 0 rather than confirmed, to avoid backpressure on the publishing loop.
+    pub async fn publish_visualization(&mut self, mqtt_cli: &mqtt::AsyncClient) {
+        self.visualization_command().publish(mqtt_cli);
+    }
+
+    /// Builds the next state publish. State updates drive master control's view of the AGV, so
+    /// they're marked QoS 1 and not fire-and-forget, unlike the best-effort visualization stream.
+    pub fn state_command(&mut self) -> PublishCommand {
         self.state.header_id += 1;
         self.state.timestamp = utils::get_timestamp();
-        
-        let serialized = serde_json::to_string(&self.state).unwrap();
-        mqtt_utils::mqtt_publish(mqtt_cli, &self.state_topic, &serialized)
-            .await
-            .unwrap();
+
+        PublishCommand {
+            topic: self.state_topic.clone(),
+            payload: serde_json::to_string(&self.state).unwrap(),
+            qos: mqtt::QOS_1,
+            retain: false,
+            correlation_data: None,
+        }
+    }
+
+    /// State updates drive master control's view of the AGV, so they're published at QoS 1
+    /// and confirmed, unlike the best-effort visualization stream.
+    pub async fn publish_state(&mut self, mqtt_cli: &mqtt::AsyncClient) {
+        self.state_command().publish_confirmed(mqtt_cli).await;
     }
 
     pub fn accept_instant_actions(&mut self, instant_action_request: InstantActions) {
@@ -267,9 +672,49 @@ impl VehicleSimulator {
                 action_description: None,
             };
             self.state.action_states.push(action_state);
+            self.action_registry.insert(instant_action.action_id.clone(), instant_action.clone());
         }
     }
 
+    /// Registers `target` as where a completion ack for `action_id` should be sent once it
+    /// reaches `Finished`/`Failed`, for an `instantActions` message that carried MQTT5
+    /// `ResponseTopic`/`CorrelationData` properties. See [`drain_completion_acks`](Self::drain_completion_acks).
+    pub fn register_pending_ack(&mut self, action_id: String, target: ResponseTarget) {
+        self.pending_acks.insert(action_id, target);
+    }
+
+    /// Builds a completion ack for every action with a [`register_pending_ack`](Self::register_pending_ack)
+    /// entry that has since reached `Finished`/`Failed`, removing it from the pending set so it
+    /// is only ever acked once. Meant to be called once per tick alongside the regular
+    /// state/visualization publishes, so a late-arriving completion is routed back to the
+    /// master control that asked for it without needing to poll the `state` topic.
+    pub fn drain_completion_acks(&mut self) -> Vec<PublishCommand> {
+        let newly_finished: Vec<(String, AckStatus)> = self
+            .state
+            .action_states
+            .iter()
+            .filter_map(|action_state| {
+                let status = match action_state.action_status {
+                    ActionStatus::Finished => AckStatus::Finished,
+                    ActionStatus::Failed => AckStatus::Failed,
+                    _ => return None,
+                };
+                self.pending_acks
+                    .contains_key(&action_state.action_id)
+                    .then(|| (action_state.action_id.clone(), status))
+            })
+            .collect();
+
+        newly_finished
+            .into_iter()
+            .filter_map(|(action_id, status)| {
+                let target = self.pending_acks.remove(&action_id)?;
+                let ack = InstantActionsAck { action_ids: vec![action_id], status };
+                Some(ack.into_command(&target))
+            })
+            .collect()
+    }
+
     pub fn process_order(&mut self, order_request: Order) {
         if order_request.order_id != self.state.order_id {
             self.handle_new_order(order_request);
@@ -291,20 +736,91 @@ impl VehicleSimulator {
         }
     }
 
+    /// Apply an order update sharing the current `order_id`. A valid update's
+    /// `order_update_id` must be exactly one past the current one, and it must still describe
+    /// the node the vehicle is currently sitting at (by `node_id`) so its released/unreleased
+    /// nodes and edges beyond that point can be stitched onto the base already in progress.
     fn handle_order_update(&mut self, order_request: Order) {
-        if order_request.order_update_id > self.state.order_update_id {
-            if !self.can_accept_new_order() {
-                return;
-            }
+        if order_request.order_update_id <= self.state.order_update_id {
+            self.reject_order(format!(
+                "Order update_id {} is stale; current order_update_id is {}",
+                order_request.order_update_id, self.state.order_update_id
+            ));
+            return;
+        }
 
-            self.state.action_states.clear();
-            self.accept_order(order_request);
-        } else {
-            self.reject_order("Order update ID is lower than current".to_string());
+        if order_request.order_update_id != self.state.order_update_id + 1 {
+            self.reject_order(format!(
+                "Order update_id {} is not contiguous with current order_update_id {}",
+                order_request.order_update_id, self.state.order_update_id
+            ));
+            return;
         }
+
+        if !self.can_accept_new_order() {
+            return;
+        }
+
+        let reference_node_id = self
+            .state
+            .node_states
+            .first()
+            .map(|node_state| node_state.node_id.clone())
+            .unwrap_or_else(|| self.state.last_node_id.clone());
+
+        let Some(shared_node_index) = order_request
+            .nodes
+            .iter()
+            .position(|node| node.node_id == reference_node_id)
+        else {
+            self.reject_order(format!(
+                "Order update does not contain node '{}', where the vehicle currently is",
+                reference_node_id
+            ));
+            return;
+        };
+
+        self.apply_order_update(order_request, shared_node_index);
+    }
+
+    /// Stitch an order update onto the base already in progress: nodes/edges at or beyond
+    /// `shared_node_index` replace their current counterparts (picking up `released` flips from
+    /// horizon to base), while everything the vehicle has already passed, and the action states
+    /// already tracked for it, are left untouched. `last_node_sequence_id` is never reset here, so
+    /// previously-horizon nodes becoming released does not interrupt vehicle motion.
+    fn apply_order_update(&mut self, order_request: Order, shared_node_index: usize) {
+        println!(
+            "Applying update {} to order: {}",
+            order_request.order_update_id, order_request.order_id
+        );
+
+        let shared_node_sequence_id = order_request.nodes[shared_node_index].sequence_id;
+        let updated_nodes = &order_request.nodes[shared_node_index..];
+        let updated_edges: Vec<&Edge> = order_request
+            .edges
+            .iter()
+            .filter(|edge| edge.sequence_id >= shared_node_sequence_id)
+            .collect();
+
+        for node in updated_nodes {
+            for action in &node.actions {
+                self.add_action_state_if_new(action);
+            }
+        }
+        for edge in &updated_edges {
+            for action in &edge.actions {
+                self.add_action_state_if_new(action);
+            }
+        }
+
+        self.state.node_states = updated_nodes.iter().map(Self::node_state_from_node).collect();
+        self.state.edge_states = updated_edges.iter().map(|&edge| Self::edge_state_from_edge(edge)).collect();
+
+        self.state.order_update_id = order_request.order_update_id;
+        self.order = Some(order_request);
     }
 
-    fn can_accept_new_order(&self) -> bool {
+    fn can_accept_new_order(&mut self) -> bool {
         let has_unreleased_nodes = self.state.node_states.iter().any(|node| !node.released);
         
         if has_unreleased_nodes && self.state.node_states[0].sequence_id != self.state.last_node_sequence_id {
@@ -343,6 +859,7 @@ impl VehicleSimulator {
 
     fn accept_order(&mut self, order_request: Order) {
         println!("Accepting order: {}", order_request.order_id);
+        self.trace(TraceEvent::OrderAccepted { order_id: order_request.order_id.clone() });
         self.order = Some(order_request);
 
         // Update order information
@@ -367,14 +884,7 @@ impl VehicleSimulator {
         let order = self.order.as_ref().unwrap();
         let nodes = order.nodes.clone();
         for node in &nodes {
-            let node_state = NodeState {
-                node_id: node.node_id.clone(),
-                sequence_id: node.sequence_id.clone(),
-                released: node.released.clone(),
-                node_description: node.node_description.clone(),
-                node_position: node.node_position.clone(),
-            };
-            self.state.node_states.push(node_state);
+            self.state.node_states.push(Self::node_state_from_node(node));
 
             // Add node actions
             for action in &node.actions {
@@ -387,14 +897,7 @@ impl VehicleSimulator {
         let order = self.order.as_ref().unwrap();
         let edges = order.edges.clone();
         for edge in &edges {
-            let edge_state = EdgeState {
-                edge_id: edge.edge_id.clone(),
-                sequence_id: edge.sequence_id.clone(),
-                released: edge.released.clone(),
-                edge_description: edge.edge_description.clone(),
-                trajectory: edge.trajectory.clone(),
-            };
-            self.state.edge_states.push(edge_state);
+            self.state.edge_states.push(Self::edge_state_from_edge(edge));
 
             // Add edge actions
             for action in &edge.actions {
@@ -403,6 +906,26 @@ impl VehicleSimulator {
         }
     }
 
+    fn node_state_from_node(node: &Node) -> NodeState {
+        NodeState {
+            node_id: node.node_id.clone(),
+            sequence_id: node.sequence_id,
+            released: node.released,
+            node_description: node.node_description.clone(),
+            node_position: node.node_position.clone(),
+        }
+    }
+
+    fn edge_state_from_edge(edge: &Edge) -> EdgeState {
+        EdgeState {
+            edge_id: edge.edge_id.clone(),
+            sequence_id: edge.sequence_id,
+            released: edge.released,
+            edge_description: edge.edge_description.clone(),
+            trajectory: edge.trajectory.clone(),
+        }
+    }
+
     fn add_action_state(&mut self, action: &Action) {
         let action_state = ActionState {
             action_id: action.action_id.clone(),
@@ -412,67 +935,145 @@ impl VehicleSimulator {
             result_description: None,
         };
         self.state.action_states.push(action_state);
+        self.action_registry.insert(action.action_id.clone(), action.clone());
+    }
+
+    /// Like [`add_action_state`](Self::add_action_state), but skips actions the vehicle is
+    /// already tracking, so re-applying an order update never resets the progress of an action
+    /// carried over from the previous base.
+    fn add_action_state_if_new(&mut self, action: &Action) {
+        if self.find_action_state_index(&action.action_id).is_none() {
+            self.add_action_state(action);
+        }
     }
 
-    fn reject_order(&self, reason: String) {
+    fn reject_order(&mut self, reason: String) {
         println!("Rejecting order: {}", reason);
+        self.trace(TraceEvent::OrderRejected { reason });
     }
 
     pub fn update_state(&mut self) {
-        if self.is_action_in_progress() {
+        self.tick += 1;
+        self.process_instant_actions();
+        self.update_battery();
+
+        if self.state.paused == Some(true) || self.is_motion_blocked() {
             return;
         }
 
-        self.process_instant_actions();
-        
         if self.order.is_none() {
             return;
         }
 
         self.process_node_actions();
+
+        if self.is_motion_blocked() {
+            return;
+        }
+
         self.update_vehicle_position();
     }
 
-    fn is_action_in_progress(&self) -> bool {
-        if let Some(start_time) = self.action_start_time {
-            let current_time = chrono::Utc::now().timestamp();
-            let action_duration = self.config.settings.action_time as i64;
-            current_time < start_time.timestamp() + action_duration
-        } else {
-            false
+    /// Fleet-aware variant of [`update_state`](Self::update_state): before committing to the
+    /// next edge, it acquires a reservation for the target node and edge from the shared
+    /// `reservations` table instead of moving unconditionally. Used by
+    /// [`FleetSimulator`](crate::fleet_simulator::FleetSimulator) so that multiple vehicles
+    /// sharing a node/edge graph never collide.
+    pub(crate) fn update_state_traffic_aware(
+        &mut self,
+        reservations: &mut ReservationTable,
+        current_tick: u64,
+    ) -> TrafficStatus {
+        self.tick += 1;
+        self.process_instant_actions();
+        self.update_battery();
+
+        if self.state.paused == Some(true) || self.is_motion_blocked() {
+            return TrafficStatus::Proceeding;
+        }
+
+        if self.order.is_none() {
+            return TrafficStatus::Proceeding;
+        }
+
+        self.process_node_actions();
+
+        if self.is_motion_blocked() {
+            return TrafficStatus::Proceeding;
+        }
+
+        let Some((node_id, edge_id)) = self.next_reservation_targets() else {
+            self.update_vehicle_position();
+            self.waiting_on = None;
+            return TrafficStatus::Proceeding;
+        };
+
+        let serial_number = self.serial_number().to_string();
+        let node_reservation = reservations.reserve_node(&node_id, &serial_number, current_tick);
+        let edge_reservation = reservations.reserve_edge(&edge_id, &serial_number, current_tick);
+
+        match (node_reservation, edge_reservation) {
+            (ReservationOutcome::Granted, ReservationOutcome::Granted) => {
+                self.update_vehicle_position();
+                self.waiting_on = None;
+                TrafficStatus::Proceeding
+            }
+            (ReservationOutcome::HeldBy(holder), _) | (_, ReservationOutcome::HeldBy(holder)) => {
+                self.waiting_on = Some(holder.clone());
+                TrafficStatus::WaitingOn(holder)
+            }
+        }
+    }
+
+    /// node_id/edge_id the vehicle would need to reserve to advance on its current order, or
+    /// `None` if it has nothing to drive toward right now (no order, already at the last node, or
+    /// the next node is still unreleased horizon).
+    fn next_reservation_targets(&self) -> Option<(String, String)> {
+        if self.state.agv_position.is_none() || self.state.node_states.len() < 2 {
+            return None;
+        }
+
+        let next_node = self.get_next_node()?;
+        if !next_node.released {
+            return None;
         }
+
+        let edge = self
+            .state
+            .edge_states
+            .iter()
+            .find(|edge| edge.sequence_id == next_node.sequence_id.saturating_sub(1))?;
+
+        Some((next_node.node_id.clone(), edge.edge_id.clone()))
     }
 
+    /// Advance every known instant action by one lifecycle step. Safe to call even without any
+    /// accepted instant actions, and regardless of pause state, so that e.g. a `resume` action
+    /// itself can still run while the vehicle is paused.
     pub fn process_instant_actions(&mut self) {
-        if let Some(instant_actions) = &self.instant_actions {
-            let actions = instant_actions.actions.clone();
-            for action in actions {
-                if let Some(action_state) = self.state.action_states.iter().find(|state| state.action_id == action.action_id) {
-                    if action_state.action_status == ActionStatus::Waiting {
-                        self.run_action(action);
-                    }
-                }
-            }
+        let Some(instant_actions) = &self.instant_actions else {
+            return;
+        };
+
+        let action_ids: Vec<String> = instant_actions.actions.iter().map(|action| action.action_id.clone()).collect();
+        for action_id in action_ids {
+            self.step_action(&action_id);
         }
     }
 
     fn process_node_actions(&mut self) {
-        if let Some(order_last_node_index) = self.find_order_last_node_index() {
-            let node_actions = &self.order.as_ref().unwrap().nodes[order_last_node_index].actions;
-            
-            if !node_actions.is_empty() {
-                for action_state in &mut self.state.action_states {
-                    for check_action in node_actions {
-                        if action_state.action_id == check_action.action_id 
-                            && action_state.action_status == ActionStatus::Waiting {
-                            println!("Executing action type: {:?}", action_state.action_type);
-                            action_state.action_status = ActionStatus::Finished;
-                            self.action_start_time = Some(chrono::Utc::now());
-                            return;
-                        }
-                    }
-                }
-            }
+        let Some(order_last_node_index) = self.find_order_last_node_index() else {
+            return;
+        };
+
+        let node_action_ids: Vec<String> = self.order.as_ref().unwrap().nodes[order_last_node_index]
+            .actions
+            .iter()
+            .map(|action| action.action_id.clone())
+            .collect();
+
+        for action_id in node_action_ids {
+            self.step_action(&action_id);
         }
     }
 
@@ -485,6 +1086,21 @@ impl VehicleSimulator {
             .position(|node| node.sequence_id == self.state.last_node_sequence_id)
     }
 
+    /// Percentage points `battery_charge` gains per tick while `charging` (via the
+    /// `"startCharging"`/`"stopCharging"` action handlers), clamped at 100%.
+    const BATTERY_CHARGE_RATE_PER_TICK: f32 = 0.5;
+
+    /// Ramp `battery_state.battery_charge` toward 100% while charging. A no-op once full or when
+    /// not currently charging.
+    fn update_battery(&mut self) {
+        if !self.state.battery_state.charging {
+            return;
+        }
+
+        self.state.battery_state.battery_charge =
+            (self.state.battery_state.battery_charge + Self::BATTERY_CHARGE_RATE_PER_TICK).min(100.0);
+    }
+
     fn update_vehicle_position(&mut self) {
         if self.state.agv_position.is_none() || self.state.node_states.is_empty() {
             return;
@@ -501,29 +1117,54 @@ impl VehicleSimulator {
             Some(node) => node,
             None => return,
         };
-        
+
         if !next_node.released {
             return;
         }
 
-        let vehicle_position = self.state.agv_position.as_ref().unwrap();
-        let next_node_position = match next_node.node_position.as_ref() {
+        let next_node_position = match next_node.node_position.clone() {
             Some(pos) => pos,
             None => return,
         };
+        let next_node_id = next_node.node_id.clone();
+        let next_node_sequence_id = next_node.sequence_id.clone();
+
+        let vehicle_position = self.state.agv_position.as_ref().unwrap().clone();
+
+        let edge_id = self
+            .state
+            .edge_states
+            .iter()
+            .find(|edge| edge.sequence_id == next_node_sequence_id.saturating_sub(1))
+            .map(|edge| edge.edge_id.clone());
+
+        if let Some(edge_id) = &edge_id {
+            if self.current_edge_id.as_ref() != Some(edge_id) {
+                self.current_edge_id = Some(edge_id.clone());
+                self.trace(TraceEvent::EdgeEntered { edge_id: edge_id.clone() });
+            }
+        }
 
-        let updated_position = self.calculate_new_position(vehicle_position, next_node_position, &next_node);
         let distance = utils::get_distance(
             vehicle_position.x,
             vehicle_position.y,
             next_node_position.x,
             next_node_position.y,
         );
+        let step = self.ramp_velocity_toward(distance);
+
+        let updated_position =
+            self.calculate_new_position(&vehicle_position, &next_node_position, edge_id.as_deref(), step);
+
+        // A vehicle standing exactly on the node has a step of 0, which `distance < step` never
+        // satisfies on its own.
+        let should_arrive = distance < step || distance == 0.0;
+
+        self.trace(TraceEvent::PositionDelta {
+            dx: updated_position.0 - vehicle_position.x,
+            dy: updated_position.1 - vehicle_position.y,
+        });
 
-        let should_arrive = distance < self.config.settings.speed + 0.1;
-        let next_node_id = next_node.node_id.clone();
-        let next_node_sequence_id = next_node.sequence_id.clone();
-        
         // Update vehicle position
         if let Some(agv_pos) = &mut self.state.agv_position {
             agv_pos.x = updated_position.0;
@@ -536,8 +1177,23 @@ impl VehicleSimulator {
             self.visualization.agv_position = Some(agv_pos.clone());
         }
 
+        // `vx`/`vy` are in vehicle coordinates: rotate the map-frame displacement by the new heading.
+        let (dx, dy) = (updated_position.0 - vehicle_position.x, updated_position.1 - vehicle_position.y);
+        let (sin_theta, cos_theta) = updated_position.2.sin_cos();
+        let velocity = Velocity {
+            vx: Some(dx * cos_theta + dy * sin_theta),
+            vy: Some(-dx * sin_theta + dy * cos_theta),
+            omega: Some(updated_position.2 - vehicle_position.theta),
+        };
+        self.state.velocity = Some(velocity.clone());
+        self.visualization.velocity = Some(velocity);
+
         // Check if reached next node
         if should_arrive {
+            if let Some(edge_id) = edge_id {
+                self.trace(TraceEvent::EdgeExited { edge_id });
+            }
+
             if !self.state.node_states.is_empty() {
                 self.state.node_states.remove(0);
             }
@@ -545,11 +1201,35 @@ impl VehicleSimulator {
                 self.state.edge_states.remove(0);
             }
 
+            self.trace(TraceEvent::NodeArrived { node_id: next_node_id.clone() });
+
             self.state.last_node_id = next_node_id;
             self.state.last_node_sequence_id = next_node_sequence_id;
+            self.trajectory_progress = None;
+            self.current_edge_id = None;
+            self.current_velocity = 0.0;
         }
     }
 
+    /// One tick of a trapezoidal speed profile: ramps `current_velocity` up toward
+    /// `Settings.speed` by `Settings.max_acceleration`, except once `remaining_distance` has
+    /// closed to the braking distance `v^2 / (2 * max_deceleration)`, at which point it ramps
+    /// down by `Settings.max_deceleration` instead so the vehicle comes to rest at the node
+    /// rather than overshooting it. Returns the resulting per-tick step distance.
+    fn ramp_velocity_toward(&mut self, remaining_distance: f32) -> f32 {
+        let max_acceleration = self.config.settings.max_acceleration;
+        let max_deceleration = self.config.settings.max_deceleration.max(f32::EPSILON);
+        let braking_distance = self.current_velocity.powi(2) / (2.0 * max_deceleration);
+
+        self.current_velocity = if remaining_distance <= braking_distance {
+            (self.current_velocity - max_deceleration).max(0.0)
+        } else {
+            (self.current_velocity + max_acceleration).min(self.config.settings.speed)
+        };
+
+        self.current_velocity
+    }
+
     fn get_next_node(&self) -> Option<&NodeState> {
         let last_node_index = self.state.node_states.iter()
             .position(|node_state| node_state.sequence_id == self.state.last_node_sequence_id)
@@ -562,41 +1242,45 @@ impl VehicleSimulator {
         Some(&self.state.node_states[last_node_index + 1])
     }
 
+    /// `edge_id` is the edge `update_vehicle_position` already resolved as the one currently being
+    /// traversed (or `None` if there isn't one), passed in rather than re-derived here so the two
+    /// don't risk disagreeing on which edge that is.
     fn calculate_new_position(
-        &self,
+        &mut self,
         vehicle_position: &AgvPosition,
         next_node_position: &NodePosition,
-        next_node: &NodeState,
+        edge_id: Option<&str>,
+        step: f32,
     ) -> (f32, f32, f32) {
-        let next_edge = self.state.edge_states.iter()
-            .find(|edge| edge.sequence_id == next_node.sequence_id - 1);
+        let spline_exhausted = self.trajectory_progress.map_or(false, |u| u >= 1.0);
+        let next_edge = edge_id.and_then(|edge_id| {
+            self.state.edge_states.iter().find(|edge| edge.edge_id == edge_id)
+        });
 
-        if let Some(edge) = next_edge {
-            if let Some(trajectory) = &edge.trajectory {
-                utils::iterate_position_with_trajectory(
-                    vehicle_position.x,
-                    vehicle_position.y,
-                    next_node_position.x,
-                    next_node_position.y,
-                    self.config.settings.speed,
-                    trajectory.clone(),
-                )
-            } else {
-                utils::iterate_position(
-                    vehicle_position.x,
-                    vehicle_position.y,
-                    next_node_position.x,
-                    next_node_position.y,
-                    self.config.settings.speed,
-                )
-            }
+        // Once the spline is exhausted (`u` reached `1.0`) the rest of the way to the node is
+        // driven in a straight line from the vehicle's own position.
+        let trajectory = next_edge
+            .and_then(|edge| edge.trajectory.as_ref())
+            .filter(|_| !spline_exhausted);
+
+        if let Some(trajectory) = trajectory {
+            let current_u = self.trajectory_progress.unwrap_or(0.0);
+            let (next_x, next_y, theta, next_u) = utils::advance_trajectory(
+                trajectory,
+                current_u,
+                next_node_position.x,
+                next_node_position.y,
+                step,
+            );
+            self.trajectory_progress = Some(next_u);
+            (next_x, next_y, theta)
         } else {
             utils::iterate_position(
                 vehicle_position.x,
                 vehicle_position.y,
                 next_node_position.x,
                 next_node_position.y,
-                self.config.settings.speed,
+                step,
             )
         }
     }
@@ -0,0 +1,262 @@
+use vda5050_vehicle_simulator::{
+    config::{AdminConfig, Config, MqttBrokerConfig, OutboundQueueConfig, PersistenceConfig, Settings, VehicleConfig},
+    protocol::vda_2_0_0::{
+        vda5050_2_0_0_action::{Action, ActionParameter, ActionParameterValue, BlockingType},
+        vda5050_2_0_0_instant_actions::InstantActions,
+        vda5050_2_0_0_state::ActionStatus,
+    },
+    utils,
+    vehicle_simulator::VehicleSimulator,
+};
+
+fn create_test_config() -> Config {
+    Config {
+        mqtt_broker: MqttBrokerConfig {
+            host: "localhost".to_string(),
+            port: "1883".to_string(),
+            vda_interface: "uagv".to_string(),
+            protocol_version: "3.1.1".to_string(),
+            reconnect_initial_backoff_ms: 500,
+            reconnect_max_backoff_secs: 30,
+            reconnect_max_attempts: 5,
+        },
+        vehicle: VehicleConfig {
+            serial_number: "TEST-AGV-001".to_string(),
+            manufacturer: "TEST".to_string(),
+            vda_version: "v2".to_string(),
+            vda_full_version: "2.0.0".to_string(),
+        },
+        settings: Settings {
+            map_id: "test_map".to_string(),
+            state_frequency: 1,
+            visualization_frequency: 5,
+            action_time: 0.0,
+            robot_count: 1,
+            speed: 0.1,
+            max_acceleration: 0.1,
+            max_deceleration: 0.1,
+        },
+        admin: AdminConfig {
+            enabled: false,
+            bind_address: "127.0.0.1:0".to_string(),
+        },
+        persistence: PersistenceConfig {
+            enabled: false,
+            path: "/tmp/test_action_handlers_state.json".to_string(),
+            save_interval_secs: 30,
+        },
+        outbound_queue: OutboundQueueConfig {
+            visualization_buffer_depth: 10,
+            drop_policy: "drop_oldest".to_string(),
+        },
+    }
+}
+
+/// Drive every known instant action through the full `Waiting -> Initializing -> Running ->
+/// Finished` lifecycle by repeatedly calling `process_instant_actions`, one step per call.
+fn drain_instant_actions(simulator: &mut VehicleSimulator) {
+    for _ in 0..10 {
+        simulator.process_instant_actions();
+        if simulator
+            .state
+            .action_states
+            .iter()
+            .all(|action_state| matches!(action_state.action_status, ActionStatus::Finished | ActionStatus::Failed))
+        {
+            return;
+        }
+    }
+}
+
+fn action_status(simulator: &VehicleSimulator, action_id: &str) -> ActionStatus {
+    simulator
+        .state
+        .action_states
+        .iter()
+        .find(|action_state| action_state.action_id == action_id)
+        .unwrap()
+        .action_status
+}
+
+fn action_result_description(simulator: &VehicleSimulator, action_id: &str) -> Option<String> {
+    simulator
+        .state
+        .action_states
+        .iter()
+        .find(|action_state| action_state.action_id == action_id)
+        .unwrap()
+        .result_description
+        .clone()
+}
+
+fn instant_actions(header_id: u32, actions: Vec<Action>) -> InstantActions {
+    InstantActions {
+        header_id,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions,
+    }
+}
+
+fn action_with_params(action_id: &str, action_type: &str, params: Vec<(&str, ActionParameterValue)>) -> Action {
+    Action {
+        action_type: action_type.to_string(),
+        action_id: action_id.to_string(),
+        action_description: None,
+        blocking_type: BlockingType::None,
+        action_parameters: Some(
+            params
+                .into_iter()
+                .map(|(key, value)| ActionParameter { key: key.to_string(), value })
+                .collect(),
+        ),
+    }
+}
+
+#[test]
+fn start_charging_sets_charging_flag() {
+    let mut simulator = VehicleSimulator::new(create_test_config());
+    simulator.state.battery_state.charging = false;
+
+    simulator.accept_instant_actions(instant_actions(
+        1,
+        vec![Action {
+            action_type: "startCharging".to_string(),
+            action_id: "charge_1".to_string(),
+            action_description: None,
+            blocking_type: BlockingType::None,
+            action_parameters: None,
+        }],
+    ));
+    drain_instant_actions(&mut simulator);
+
+    assert!(simulator.state.battery_state.charging);
+    assert_eq!(action_status(&simulator, "charge_1"), ActionStatus::Finished);
+    assert_eq!(action_result_description(&simulator, "charge_1"), Some("Charging started".to_string()));
+}
+
+#[test]
+fn stop_charging_clears_charging_flag_and_reports_battery_level() {
+    let mut simulator = VehicleSimulator::new(create_test_config());
+    simulator.state.battery_state.charging = true;
+    simulator.state.battery_state.battery_charge = 42.0;
+
+    simulator.accept_instant_actions(instant_actions(
+        1,
+        vec![Action {
+            action_type: "stopCharging".to_string(),
+            action_id: "charge_1".to_string(),
+            action_description: None,
+            blocking_type: BlockingType::None,
+            action_parameters: None,
+        }],
+    ));
+    drain_instant_actions(&mut simulator);
+
+    assert!(!simulator.state.battery_state.charging);
+    assert_eq!(action_result_description(&simulator, "charge_1"), Some("Charging stopped at 42.0%".to_string()));
+}
+
+#[test]
+fn pick_adds_a_load_from_action_parameters() {
+    let mut simulator = VehicleSimulator::new(create_test_config());
+    assert!(simulator.state.loads.is_empty());
+
+    simulator.accept_instant_actions(instant_actions(
+        1,
+        vec![action_with_params(
+            "pick_1",
+            "pick",
+            vec![
+                ("loadId", ActionParameterValue::Str("load-1".to_string())),
+                ("loadType", ActionParameterValue::Str("pallet".to_string())),
+                ("loadPosition", ActionParameterValue::Str("front".to_string())),
+            ],
+        )],
+    ));
+    drain_instant_actions(&mut simulator);
+
+    assert_eq!(simulator.state.loads.len(), 1);
+    assert_eq!(simulator.state.loads[0].load_id.as_deref(), Some("load-1"));
+    assert_eq!(simulator.state.loads[0].load_type.as_deref(), Some("pallet"));
+    assert_eq!(action_status(&simulator, "pick_1"), ActionStatus::Finished);
+}
+
+#[test]
+fn drop_with_load_id_removes_only_the_matching_load() {
+    let mut simulator = VehicleSimulator::new(create_test_config());
+    simulator.accept_instant_actions(instant_actions(
+        1,
+        vec![
+            action_with_params("pick_1", "pick", vec![("loadId", ActionParameterValue::Str("load-1".to_string()))]),
+        ],
+    ));
+    drain_instant_actions(&mut simulator);
+    simulator.accept_instant_actions(instant_actions(
+        2,
+        vec![
+            action_with_params("pick_2", "pick", vec![("loadId", ActionParameterValue::Str("load-2".to_string()))]),
+        ],
+    ));
+    drain_instant_actions(&mut simulator);
+    assert_eq!(simulator.state.loads.len(), 2);
+
+    simulator.accept_instant_actions(instant_actions(
+        3,
+        vec![action_with_params("drop_1", "drop", vec![("loadId", ActionParameterValue::Str("load-1".to_string()))])],
+    ));
+    drain_instant_actions(&mut simulator);
+
+    assert_eq!(simulator.state.loads.len(), 1);
+    assert_eq!(simulator.state.loads[0].load_id.as_deref(), Some("load-2"));
+}
+
+#[test]
+fn drop_without_load_id_clears_every_load() {
+    let mut simulator = VehicleSimulator::new(create_test_config());
+    simulator.accept_instant_actions(instant_actions(
+        1,
+        vec![action_with_params("pick_1", "pick", vec![("loadId", ActionParameterValue::Str("load-1".to_string()))])],
+    ));
+    drain_instant_actions(&mut simulator);
+    assert_eq!(simulator.state.loads.len(), 1);
+
+    simulator.accept_instant_actions(instant_actions(
+        2,
+        vec![Action {
+            action_type: "drop".to_string(),
+            action_id: "drop_1".to_string(),
+            action_description: None,
+            blocking_type: BlockingType::None,
+            action_parameters: None,
+        }],
+    ));
+    drain_instant_actions(&mut simulator);
+
+    assert!(simulator.state.loads.is_empty());
+}
+
+#[test]
+fn factsheet_request_acknowledges_without_modeling_a_factsheet_message() {
+    let mut simulator = VehicleSimulator::new(create_test_config());
+
+    simulator.accept_instant_actions(instant_actions(
+        1,
+        vec![Action {
+            action_type: "factsheetRequest".to_string(),
+            action_id: "factsheet_1".to_string(),
+            action_description: None,
+            blocking_type: BlockingType::None,
+            action_parameters: None,
+        }],
+    ));
+    drain_instant_actions(&mut simulator);
+
+    assert_eq!(action_status(&simulator, "factsheet_1"), ActionStatus::Finished);
+    assert_eq!(
+        action_result_description(&simulator, "factsheet_1"),
+        Some("Factsheet publishing is not modeled by this simulator".to_string())
+    );
+}
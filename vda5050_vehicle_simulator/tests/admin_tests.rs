@@ -0,0 +1,96 @@
+use vda5050_vehicle_simulator::admin::{render_prometheus, MetricsSnapshot};
+use vda5050_vehicle_simulator::protocol::vda_2_0_0::vda5050_2_0_0_state::ActionStatus;
+
+fn test_snapshot() -> MetricsSnapshot {
+    MetricsSnapshot {
+        serial_number: "TEST-AGV-001".to_string(),
+        battery_charge: 87.5,
+        driving: true,
+        order_id: "order-1".to_string(),
+        order_update_id: 2,
+        pending_node_states: 3,
+        pending_edge_states: 2,
+        action_states_by_status: vec![
+            (ActionStatus::Waiting, 1),
+            (ActionStatus::Initializing, 0),
+            (ActionStatus::Running, 2),
+            (ActionStatus::Paused, 0),
+            (ActionStatus::Finished, 4),
+            (ActionStatus::Failed, 0),
+        ],
+        connection_header_id: 5,
+        state_header_id: 10,
+        visualization_header_id: 20,
+        x: 1.5,
+        y: -2.5,
+        theta: 0.75,
+    }
+}
+
+#[test]
+fn renders_every_gauge_labeled_with_serial_number() {
+    let output = render_prometheus(&test_snapshot());
+
+    for metric in [
+        "agv_battery_charge",
+        "agv_driving",
+        "agv_order_update_id",
+        "agv_pending_node_states",
+        "agv_pending_edge_states",
+        "agv_connection_header_id",
+        "agv_state_header_id",
+        "agv_visualization_header_id",
+        "agv_position_x",
+        "agv_position_y",
+        "agv_position_theta",
+    ] {
+        assert!(
+            output.contains(&format!("{metric}{{serial_number=\"TEST-AGV-001\"}}")),
+            "missing or unlabeled series: {metric}\n{output}"
+        );
+    }
+}
+
+#[test]
+fn renders_gauge_values() {
+    let output = render_prometheus(&test_snapshot());
+
+    assert!(output.contains("agv_battery_charge{serial_number=\"TEST-AGV-001\"} 87.5"));
+    assert!(output.contains("agv_driving{serial_number=\"TEST-AGV-001\"} 1"));
+    assert!(output.contains("agv_pending_node_states{serial_number=\"TEST-AGV-001\"} 3"));
+    assert!(output.contains("agv_pending_edge_states{serial_number=\"TEST-AGV-001\"} 2"));
+}
+
+#[test]
+fn renders_one_action_states_series_per_status_including_zero_counts() {
+    let output = render_prometheus(&test_snapshot());
+
+    assert!(output.contains("agv_action_states{serial_number=\"TEST-AGV-001\",action_status=\"Waiting\"} 1"));
+    assert!(output.contains("agv_action_states{serial_number=\"TEST-AGV-001\",action_status=\"Initializing\"} 0"));
+    assert!(output.contains("agv_action_states{serial_number=\"TEST-AGV-001\",action_status=\"Running\"} 2"));
+    assert!(output.contains("agv_action_states{serial_number=\"TEST-AGV-001\",action_status=\"Finished\"} 4"));
+}
+
+#[test]
+fn escapes_nothing_but_still_separates_distinct_vehicles_by_label() {
+    let mut other = test_snapshot();
+    other.serial_number = "TEST-AGV-002".to_string();
+    other.battery_charge = 42.0;
+
+    let first = render_prometheus(&test_snapshot());
+    let second = render_prometheus(&other);
+
+    assert!(first.contains("serial_number=\"TEST-AGV-001\""));
+    assert!(second.contains("serial_number=\"TEST-AGV-002\""));
+    assert!(!first.contains("TEST-AGV-002"));
+}
+
+#[test]
+fn metrics_snapshot_serializes_camel_case_for_state_endpoint() {
+    let json = serde_json::to_value(test_snapshot()).unwrap();
+
+    assert_eq!(json["serialNumber"], "TEST-AGV-001");
+    assert_eq!(json["batteryCharge"], 87.5);
+    assert_eq!(json["orderUpdateId"], 2);
+    assert_eq!(json["pendingNodeStates"], 3);
+}
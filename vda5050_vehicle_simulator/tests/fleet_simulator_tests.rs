@@ -0,0 +1,195 @@
+use vda5050_vehicle_simulator::{
+    config::{AdminConfig, Config, OutboundQueueConfig, PersistenceConfig, MqttBrokerConfig, Settings, VehicleConfig},
+    fleet_simulator::{FleetSimulator, TrafficStatus},
+    protocol::vda5050_common::NodePosition,
+    protocol::vda_2_0_0::{
+        vda5050_2_0_0_action::{Action, ActionParameter, ActionParameterValue, BlockingType},
+        vda5050_2_0_0_instant_actions::InstantActions,
+        vda5050_2_0_0_order::{Edge, Node, Order},
+    },
+    utils,
+    vehicle_simulator::VehicleSimulator,
+};
+
+fn config_for(serial_number: &str) -> Config {
+    Config {
+        mqtt_broker: MqttBrokerConfig {
+            host: "localhost".to_string(),
+            port: "1883".to_string(),
+            vda_interface: "uagv".to_string(),
+            protocol_version: "3.1.1".to_string(),
+            reconnect_initial_backoff_ms: 500,
+            reconnect_max_backoff_secs: 30,
+            reconnect_max_attempts: 5,
+        },
+        vehicle: VehicleConfig {
+            serial_number: serial_number.to_string(),
+            manufacturer: "TEST".to_string(),
+            vda_version: "v2".to_string(),
+            vda_full_version: "2.0.0".to_string(),
+        },
+        settings: Settings {
+            map_id: "test_map".to_string(),
+            state_frequency: 1,
+            visualization_frequency: 5,
+            action_time: 0.0,
+            robot_count: 2,
+            speed: 0.5,
+            max_acceleration: 0.5,
+            max_deceleration: 0.5,
+        },
+        admin: AdminConfig {
+            enabled: false,
+            bind_address: "127.0.0.1:0".to_string(),
+        },
+        persistence: PersistenceConfig {
+            enabled: false,
+            path: "/tmp/test_vehicle_state.json".to_string(),
+            save_interval_secs: 30,
+        },
+        outbound_queue: OutboundQueueConfig {
+            visualization_buffer_depth: 10,
+            drop_policy: "drop_oldest".to_string(),
+        },
+    }
+}
+
+fn node(node_id: &str, sequence_id: u32, x: f32, y: f32) -> Node {
+    Node {
+        node_id: node_id.to_string(),
+        sequence_id,
+        node_description: None,
+        released: true,
+        node_position: Some(NodePosition {
+            x,
+            y,
+            theta: None,
+            allowed_deviation_xy: Some(0.1),
+            allowed_deviation_theta: None,
+            map_id: "test_map".to_string(),
+            map_description: None,
+        }),
+        actions: vec![],
+    }
+}
+
+fn corridor_edge(start_node_id: &str, end_node_id: &str) -> Edge {
+    Edge {
+        edge_id: "corridor".to_string(),
+        sequence_id: 0,
+        edge_description: None,
+        released: true,
+        start_node_id: start_node_id.to_string(),
+        end_node_id: end_node_id.to_string(),
+        max_speed: None,
+        max_height: None,
+        min_height: None,
+        orientation: None,
+        orientation_type: None,
+        direction: None,
+        rotation_allowed: None,
+        max_rotation_speed: None,
+        length: None,
+        trajectory: None,
+        actions: vec![],
+    }
+}
+
+fn init_position(serial_number: &str, x: f32, y: f32, last_node_id: &str) -> VehicleSimulator {
+    let mut simulator = VehicleSimulator::new(config_for(serial_number));
+
+    let init_action = Action {
+        action_type: "initPosition".to_string(),
+        action_id: "init".to_string(),
+        action_description: None,
+        blocking_type: BlockingType::Hard,
+        action_parameters: Some(vec![
+            ActionParameter { key: "x".to_string(), value: ActionParameterValue::Float(x) },
+            ActionParameter { key: "y".to_string(), value: ActionParameterValue::Float(y) },
+            ActionParameter { key: "theta".to_string(), value: ActionParameterValue::Float(0.0) },
+            ActionParameter { key: "mapId".to_string(), value: ActionParameterValue::Str("test_map".to_string()) },
+            ActionParameter { key: "lastNodeId".to_string(), value: ActionParameterValue::Str(last_node_id.to_string()) },
+        ]),
+    };
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 1,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: serial_number.to_string(),
+        actions: vec![init_action],
+    });
+    // Waiting -> Initializing -> Running (the handler sets the position here) -> Finished.
+    for _ in 0..3 {
+        simulator.process_instant_actions();
+    }
+
+    simulator
+}
+
+/// Two vehicles ordered head-on across a single shared corridor edge should not both stall
+/// forever: one wins the reservation, the other yields and proceeds once it is released.
+#[test]
+fn test_two_vehicles_cross_shared_corridor_without_deadlock() {
+    let mut vehicle_a = init_position("AGV-A", 0.0, 0.0, "west");
+    vehicle_a.process_order(Order {
+        header_id: 1,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "AGV-A".to_string(),
+        order_id: "order_a".to_string(),
+        order_update_id: 0,
+        zone_set_id: None,
+        nodes: vec![node("west", 0, 0.0, 0.0), node("east", 1, 5.0, 0.0)],
+        edges: vec![corridor_edge("west", "east")],
+    });
+
+    let mut vehicle_b = init_position("AGV-B", 5.0, 0.0, "east");
+    vehicle_b.process_order(Order {
+        header_id: 1,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "AGV-B".to_string(),
+        order_id: "order_b".to_string(),
+        order_update_id: 0,
+        zone_set_id: None,
+        nodes: vec![node("east", 0, 5.0, 0.0), node("west", 1, 0.0, 0.0)],
+        edges: vec![corridor_edge("east", "west")],
+    });
+
+    let mut fleet = FleetSimulator::new(vec![vehicle_a, vehicle_b]);
+
+    let mut saw_a_wait = false;
+    let mut saw_b_wait = false;
+    for _ in 0..200 {
+        let statuses = fleet.tick();
+        if matches!(statuses[0], TrafficStatus::WaitingOn(_)) {
+            saw_a_wait = true;
+        }
+        if matches!(statuses[1], TrafficStatus::WaitingOn(_)) {
+            saw_b_wait = true;
+        }
+
+        let both_done = fleet.vehicles().iter().all(|vehicle| {
+            // Access via the public state field: both node/edge states drain to empty on completion.
+            vehicle.state.node_states.is_empty() && vehicle.state.edge_states.is_empty()
+        });
+        if both_done {
+            break;
+        }
+    }
+
+    for vehicle in fleet.vehicles() {
+        assert!(
+            vehicle.state.node_states.is_empty() && vehicle.state.edge_states.is_empty(),
+            "vehicle {} never completed its order (stalled)",
+            vehicle.serial_number()
+        );
+    }
+
+    // The corridor is a single shared resource crossed in opposite directions, so contention
+    // (and a yield from at least one side) should actually have happened in this scenario.
+    assert!(saw_a_wait || saw_b_wait, "expected reservation contention on the shared corridor");
+}
@@ -0,0 +1,90 @@
+use paho_mqtt as mqtt;
+use paho_mqtt::PropertyCode;
+
+use vda5050_vehicle_simulator::mqtt_ack::{AckStatus, InstantActionsAck, ResponseTarget};
+
+fn message_with_response_properties(response_topic: Option<&str>, correlation_data: Option<&[u8]>) -> mqtt::Message {
+    let mut props = mqtt::Properties::new();
+    if let Some(topic) = response_topic {
+        props.push_string(PropertyCode::ResponseTopic, topic).unwrap();
+    }
+    if let Some(data) = correlation_data {
+        props.push_binary(PropertyCode::CorrelationData, data.to_vec()).unwrap();
+    }
+
+    mqtt::MessageBuilder::new()
+        .topic("uagv/v2/TEST/TEST-AGV-001/instantActions")
+        .payload("{}")
+        .properties(props)
+        .finalize()
+}
+
+#[test]
+fn response_target_reads_response_topic_and_correlation_data() {
+    let msg = message_with_response_properties(Some("uagv/v2/TEST/TEST-AGV-001/ack"), Some(&[1, 2, 3]));
+
+    let target = ResponseTarget::from_message(&msg).expect("both properties are present");
+
+    assert_eq!(target.topic, "uagv/v2/TEST/TEST-AGV-001/ack");
+    assert_eq!(target.correlation_data, vec![1, 2, 3]);
+}
+
+#[test]
+fn response_target_is_none_without_a_response_topic() {
+    let msg = message_with_response_properties(None, Some(&[1, 2, 3]));
+
+    assert!(ResponseTarget::from_message(&msg).is_none());
+}
+
+#[test]
+fn response_target_is_none_without_correlation_data() {
+    let msg = message_with_response_properties(Some("uagv/v2/TEST/TEST-AGV-001/ack"), None);
+
+    assert!(ResponseTarget::from_message(&msg).is_none());
+}
+
+#[test]
+fn response_target_is_none_for_a_message_with_no_mqtt5_properties_at_all() {
+    let msg = mqtt::MessageBuilder::new()
+        .topic("uagv/v2/TEST/TEST-AGV-001/instantActions")
+        .payload("{}")
+        .finalize();
+
+    assert!(ResponseTarget::from_message(&msg).is_none());
+}
+
+#[test]
+fn into_command_echoes_the_target_topic_and_correlation_data() {
+    let target = ResponseTarget { topic: "uagv/v2/TEST/TEST-AGV-001/ack".to_string(), correlation_data: vec![9, 8, 7] };
+    let ack = InstantActionsAck { action_ids: vec!["action_1".to_string()], status: AckStatus::Accepted };
+
+    let command = ack.into_command(&target);
+
+    assert_eq!(command.topic, "uagv/v2/TEST/TEST-AGV-001/ack");
+    assert_eq!(command.correlation_data, Some(vec![9, 8, 7]));
+    assert_eq!(command.qos, mqtt::QOS_1);
+    assert!(!command.retain);
+}
+
+#[test]
+fn into_command_payload_is_camel_case_json() {
+    let target = ResponseTarget { topic: "ack".to_string(), correlation_data: vec![] };
+    let ack = InstantActionsAck {
+        action_ids: vec!["action_1".to_string(), "action_2".to_string()],
+        status: AckStatus::Finished,
+    };
+
+    let command = ack.into_command(&target);
+    let payload: serde_json::Value = serde_json::from_str(&command.payload).unwrap();
+
+    assert_eq!(payload["actionIds"], serde_json::json!(["action_1", "action_2"]));
+    assert_eq!(payload["status"], "FINISHED");
+}
+
+#[test]
+fn ack_status_serializes_as_screaming_snake_case() {
+    assert_eq!(serde_json::to_value(AckStatus::Accepted).unwrap(), "ACCEPTED");
+    assert_eq!(serde_json::to_value(AckStatus::Rejected).unwrap(), "REJECTED");
+    assert_eq!(serde_json::to_value(AckStatus::Finished).unwrap(), "FINISHED");
+    assert_eq!(serde_json::to_value(AckStatus::Failed).unwrap(), "FAILED");
+}
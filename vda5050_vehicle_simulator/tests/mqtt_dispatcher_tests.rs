@@ -0,0 +1,53 @@
+use vda5050_vehicle_simulator::mqtt_dispatcher::{vehicle_key, vehicle_key_from_topic};
+use vda5050_vehicle_simulator::mqtt_utils::generate_vda_mqtt_base_topic;
+
+#[test]
+fn vehicle_key_joins_manufacturer_and_serial_number() {
+    assert_eq!(vehicle_key("TEST", "TEST-AGV-001"), "TEST/TEST-AGV-001");
+}
+
+#[test]
+fn vehicle_key_from_topic_parses_order_and_instant_actions_topics() {
+    assert_eq!(
+        vehicle_key_from_topic("uagv/v2/TEST/TEST-AGV-001/order"),
+        Some("TEST/TEST-AGV-001".to_string())
+    );
+    assert_eq!(
+        vehicle_key_from_topic("uagv/v2/TEST/TEST-AGV-001/instantActions"),
+        Some("TEST/TEST-AGV-001".to_string())
+    );
+}
+
+#[test]
+fn vehicle_key_from_topic_round_trips_with_generate_vda_mqtt_base_topic() {
+    let base_topic = generate_vda_mqtt_base_topic("uagv", "v2", "TEST", "TEST-AGV-001");
+
+    assert_eq!(
+        vehicle_key_from_topic(&format!("{}/order", base_topic)),
+        Some(vehicle_key("TEST", "TEST-AGV-001"))
+    );
+}
+
+#[test]
+fn vehicle_key_from_topic_ignores_everything_past_the_serial_number_segment() {
+    let with_extra_segments = vehicle_key_from_topic("uagv/v2/TEST/TEST-AGV-001/order/extra/segments");
+    let without_extra_segments = vehicle_key_from_topic("uagv/v2/TEST/TEST-AGV-001/order");
+
+    assert_eq!(with_extra_segments, without_extra_segments);
+}
+
+#[test]
+fn vehicle_key_from_topic_returns_none_for_topics_missing_the_serial_number_segment() {
+    assert_eq!(vehicle_key_from_topic("uagv/v2/TEST"), None);
+    assert_eq!(vehicle_key_from_topic(""), None);
+}
+
+#[test]
+fn vehicle_key_from_topic_distinguishes_vehicles_by_manufacturer_and_serial_number() {
+    let first = vehicle_key_from_topic("uagv/v2/TEST/TEST-AGV-001/order");
+    let second = vehicle_key_from_topic("uagv/v2/TEST/TEST-AGV-002/order");
+    let different_manufacturer = vehicle_key_from_topic("uagv/v2/OTHER/TEST-AGV-001/order");
+
+    assert_ne!(first, second);
+    assert_ne!(first, different_manufacturer);
+}
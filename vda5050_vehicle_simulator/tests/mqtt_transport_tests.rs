@@ -0,0 +1,155 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use vda5050_vehicle_simulator::mqtt_transport::{backoff_ceiling, backoff_duration, reconnect_with_backoff, BackoffConfig, ReconnectOutcome};
+
+const TEST_BACKOFF: BackoffConfig = BackoffConfig {
+    initial_backoff: Duration::from_millis(500),
+    max_backoff: Duration::from_secs(30),
+    max_attempts: 5,
+};
+
+fn backoff_with_max_attempts(max_attempts: u32) -> BackoffConfig {
+    BackoffConfig {
+        max_attempts,
+        ..TEST_BACKOFF
+    }
+}
+
+/// No real sleeping in tests: just records how long each requested delay was.
+async fn record_sleep(delays: &Rc<RefCell<Vec<Duration>>>, duration: Duration) {
+    delays.borrow_mut().push(duration);
+}
+
+/// Asserts `delay` falls within `backoff_duration`'s jittered range for `attempt`: somewhere
+/// between half of [`backoff_ceiling`] and the ceiling itself, inclusive.
+fn assert_in_jittered_range(delay: Duration, attempt: u32, backoff: BackoffConfig) {
+    let ceiling = backoff_ceiling(attempt, backoff.initial_backoff, backoff.max_backoff);
+    let floor = Duration::from_millis(ceiling.as_millis() as u64 / 2);
+    assert!(
+        delay >= floor && delay <= ceiling,
+        "expected delay {:?} to fall within [{:?}, {:?}] for attempt {}",
+        delay,
+        floor,
+        ceiling,
+        attempt
+    );
+}
+
+#[tokio::test]
+async fn test_reconnect_succeeds_on_first_attempt() {
+    let delays = Rc::new(RefCell::new(Vec::new()));
+
+    let outcome = reconnect_with_backoff(
+        TEST_BACKOFF,
+        || async { Ok::<(), ()>(()) },
+        || async { Ok::<(), ()>(()) },
+        |d| record_sleep(&delays, d),
+    )
+    .await;
+
+    assert_eq!(outcome, ReconnectOutcome::Reconnected { attempts: 1 });
+    assert!(delays.borrow().is_empty(), "should not sleep before the first attempt");
+}
+
+#[tokio::test]
+async fn test_reconnect_retries_with_backoff_then_succeeds() {
+    let connect_calls = Rc::new(RefCell::new(0));
+    let delays = Rc::new(RefCell::new(Vec::new()));
+
+    let outcome = reconnect_with_backoff(
+        TEST_BACKOFF,
+        || {
+            let connect_calls = Rc::clone(&connect_calls);
+            async move {
+                let mut calls = connect_calls.borrow_mut();
+                *calls += 1;
+                if *calls < 3 {
+                    Err(())
+                } else {
+                    Ok(())
+                }
+            }
+        },
+        || async { Ok::<(), ()>(()) },
+        |d| record_sleep(&delays, d),
+    )
+    .await;
+
+    assert_eq!(outcome, ReconnectOutcome::Reconnected { attempts: 3 });
+    let delays = delays.borrow();
+    assert_eq!(delays.len(), 2);
+    assert_in_jittered_range(delays[0], 0, TEST_BACKOFF);
+    assert_in_jittered_range(delays[1], 1, TEST_BACKOFF);
+}
+
+#[tokio::test]
+async fn test_reconnect_resubscribes_after_reconnecting() {
+    let subscribe_calls = Rc::new(RefCell::new(0));
+    let delays = Rc::new(RefCell::new(Vec::new()));
+
+    let outcome = reconnect_with_backoff(
+        backoff_with_max_attempts(3),
+        || async { Ok::<(), ()>(()) },
+        || {
+            let subscribe_calls = Rc::clone(&subscribe_calls);
+            async move {
+                *subscribe_calls.borrow_mut() += 1;
+                Ok(())
+            }
+        },
+        |d| record_sleep(&delays, d),
+    )
+    .await;
+
+    assert_eq!(outcome, ReconnectOutcome::Reconnected { attempts: 1 });
+    assert_eq!(*subscribe_calls.borrow(), 1, "subscribe must run once the reconnect succeeds");
+}
+
+#[tokio::test]
+async fn test_reconnect_exhausted_after_max_attempts() {
+    let delays = Rc::new(RefCell::new(Vec::new()));
+
+    let outcome = reconnect_with_backoff(
+        backoff_with_max_attempts(3),
+        || async { Err::<(), ()>(()) },
+        || async { Ok::<(), ()>(()) },
+        |d| record_sleep(&delays, d),
+    )
+    .await;
+
+    assert_eq!(outcome, ReconnectOutcome::Exhausted { attempts: 3 });
+    assert_eq!(delays.borrow().len(), 2, "one backoff sleep between each of the 3 attempts");
+}
+
+#[test]
+fn test_backoff_ceiling_doubles_then_clamps() {
+    let initial = Duration::from_millis(500);
+    let max = Duration::from_secs(30);
+
+    assert_eq!(backoff_ceiling(0, initial, max), Duration::from_millis(500));
+    assert_eq!(backoff_ceiling(1, initial, max), Duration::from_millis(1000));
+    assert_eq!(backoff_ceiling(2, initial, max), Duration::from_millis(2000));
+    assert_eq!(backoff_ceiling(20, initial, max), Duration::from_secs(30), "must clamp to the configured max");
+}
+
+#[test]
+fn test_backoff_duration_is_jittered_within_ceiling() {
+    let initial = Duration::from_millis(500);
+    let max = Duration::from_secs(30);
+
+    // Sample many draws per attempt: every one must land within [ceiling/2, ceiling], and across
+    // enough draws we should see some spread rather than the same value every time (i.e. jitter
+    // is actually being applied, not silently a no-op).
+    for attempt in [0, 1, 2, 20] {
+        let ceiling = backoff_ceiling(attempt, initial, max);
+        let floor = Duration::from_millis(ceiling.as_millis() as u64 / 2);
+
+        let samples: Vec<Duration> = (0..50).map(|_| backoff_duration(attempt, initial, max)).collect();
+        for sample in &samples {
+            assert!(*sample >= floor && *sample <= ceiling, "attempt {}: {:?} not in [{:?}, {:?}]", attempt, sample, floor, ceiling);
+        }
+        assert!(samples.iter().any(|s| *s != samples[0]), "attempt {}: expected jitter to vary across draws", attempt);
+    }
+}
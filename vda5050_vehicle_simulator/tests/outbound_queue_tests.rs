@@ -0,0 +1,127 @@
+use vda5050_vehicle_simulator::config::OutboundQueueConfig;
+use vda5050_vehicle_simulator::mqtt_dispatcher::PublishCommand;
+use vda5050_vehicle_simulator::outbound_queue::OutboundQueue;
+
+fn config(visualization_buffer_depth: usize, drop_policy: &str) -> OutboundQueueConfig {
+    OutboundQueueConfig { visualization_buffer_depth, drop_policy: drop_policy.to_string() }
+}
+
+fn command(topic: &str, payload: &str) -> PublishCommand {
+    PublishCommand { topic: topic.to_string(), payload: payload.to_string(), qos: 1, retain: false, correlation_data: None }
+}
+
+#[test]
+fn new_queue_is_empty() {
+    let queue = OutboundQueue::new(&config(10, "drop_oldest"));
+
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn buffering_a_state_command_makes_the_queue_non_empty() {
+    let mut queue = OutboundQueue::new(&config(10, "drop_oldest"));
+
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/state", "{}"));
+
+    assert!(!queue.is_empty());
+}
+
+#[test]
+fn latest_by_topic_lane_keeps_only_the_most_recent_command_per_topic() {
+    let mut queue = OutboundQueue::new(&config(10, "drop_oldest"));
+
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/state", "first"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/state", "second"));
+
+    let drained = queue.drain();
+
+    assert_eq!(drained.len(), 1);
+    assert_eq!(drained[0].payload, "second");
+}
+
+#[test]
+fn latest_by_topic_lane_keeps_one_entry_per_distinct_topic() {
+    let mut queue = OutboundQueue::new(&config(10, "drop_oldest"));
+
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/state", "state"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/connection", "connection"));
+
+    let drained = queue.drain();
+
+    assert_eq!(drained.len(), 2);
+}
+
+#[test]
+fn visualization_topics_are_ring_buffered_in_order() {
+    let mut queue = OutboundQueue::new(&config(3, "drop_oldest"));
+
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "1"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "2"));
+
+    let drained = queue.drain();
+
+    assert_eq!(drained.iter().map(|c| c.payload.as_str()).collect::<Vec<_>>(), vec!["1", "2"]);
+}
+
+#[test]
+fn drop_oldest_policy_discards_the_oldest_visualization_frame_once_full() {
+    let mut queue = OutboundQueue::new(&config(2, "drop_oldest"));
+
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "1"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "2"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "3"));
+
+    let drained = queue.drain();
+
+    assert_eq!(drained.iter().map(|c| c.payload.as_str()).collect::<Vec<_>>(), vec!["2", "3"]);
+}
+
+#[test]
+fn keep_latest_only_policy_discards_every_prior_frame_once_full() {
+    let mut queue = OutboundQueue::new(&config(2, "keep_latest_only"));
+
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "1"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "2"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "3"));
+
+    let drained = queue.drain();
+
+    assert_eq!(drained.iter().map(|c| c.payload.as_str()).collect::<Vec<_>>(), vec!["3"]);
+}
+
+#[test]
+fn unrecognized_drop_policy_strings_default_to_drop_oldest() {
+    let mut queue = OutboundQueue::new(&config(2, "totally-not-a-real-policy"));
+
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "1"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "2"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "3"));
+
+    let drained = queue.drain();
+
+    assert_eq!(drained.iter().map(|c| c.payload.as_str()).collect::<Vec<_>>(), vec!["2", "3"]);
+}
+
+#[test]
+fn ring_buffer_lanes_are_independent_per_topic() {
+    let mut queue = OutboundQueue::new(&config(1, "drop_oldest"));
+
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "agv-1"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-002/visualization", "agv-2"));
+
+    let mut drained = queue.drain();
+    drained.sort_by(|a, b| a.payload.cmp(&b.payload));
+
+    assert_eq!(drained.iter().map(|c| c.payload.as_str()).collect::<Vec<_>>(), vec!["agv-1", "agv-2"]);
+}
+
+#[test]
+fn draining_empties_the_queue() {
+    let mut queue = OutboundQueue::new(&config(10, "drop_oldest"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/state", "{}"));
+    queue.buffer(command("uagv/v2/TEST/TEST-AGV-001/visualization", "frame"));
+
+    queue.drain();
+
+    assert!(queue.is_empty());
+}
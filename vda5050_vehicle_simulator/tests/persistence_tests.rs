@@ -0,0 +1,141 @@
+use std::fs;
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use vda5050_vehicle_simulator::config::{
+    AdminConfig, Config, MqttBrokerConfig, OutboundQueueConfig, PersistenceConfig, Settings, VehicleConfig,
+};
+use vda5050_vehicle_simulator::fleet_simulator::FleetSimulator;
+use vda5050_vehicle_simulator::persistence::{self, PersistedState};
+use vda5050_vehicle_simulator::protocol::vda5050_common::AgvPosition;
+use vda5050_vehicle_simulator::protocol::vda_2_0_0::vda5050_2_0_0_state::BatteryState;
+use vda5050_vehicle_simulator::vehicle_simulator::VehicleSimulator;
+
+fn create_test_config(persistence_path: &str) -> Config {
+    Config {
+        mqtt_broker: MqttBrokerConfig {
+            host: "localhost".to_string(),
+            port: "1883".to_string(),
+            vda_interface: "uagv".to_string(),
+            protocol_version: "3.1.1".to_string(),
+            reconnect_initial_backoff_ms: 500,
+            reconnect_max_backoff_secs: 30,
+            reconnect_max_attempts: 5,
+        },
+        vehicle: VehicleConfig {
+            serial_number: "TEST-AGV-001".to_string(),
+            manufacturer: "TEST".to_string(),
+            vda_version: "v2".to_string(),
+            vda_full_version: "2.0.0".to_string(),
+        },
+        settings: Settings {
+            map_id: "test_map".to_string(),
+            state_frequency: 1,
+            visualization_frequency: 5,
+            action_time: 0.0,
+            robot_count: 1,
+            speed: 0.1,
+            max_acceleration: 0.1,
+            max_deceleration: 0.1,
+        },
+        admin: AdminConfig {
+            enabled: false,
+            bind_address: "127.0.0.1:0".to_string(),
+        },
+        persistence: PersistenceConfig {
+            enabled: true,
+            path: persistence_path.to_string(),
+            save_interval_secs: 30,
+        },
+        outbound_queue: OutboundQueueConfig {
+            visualization_buffer_depth: 10,
+            drop_policy: "drop_oldest".to_string(),
+        },
+    }
+}
+
+fn sample_persisted_state() -> PersistedState {
+    PersistedState {
+        agv_position: AgvPosition {
+            x: 1.0,
+            y: 2.0,
+            position_initialized: true,
+            theta: 0.5,
+            map_id: "test_map".to_string(),
+            deviation_range: None,
+            map_description: None,
+            localization_score: None,
+        },
+        order_id: "order-1".to_string(),
+        order_update_id: 3,
+        last_node_id: "n2".to_string(),
+        last_node_sequence_id: 2,
+        node_states: vec![],
+        edge_states: vec![],
+        action_states: vec![],
+        battery_state: BatteryState {
+            battery_charge: 80.0,
+            battery_voltage: None,
+            battery_health: None,
+            charging: false,
+            reach: None,
+        },
+    }
+}
+
+#[test]
+fn load_snapshot_returns_none_when_file_is_missing() {
+    assert!(persistence::load_snapshot("/tmp/nonexistent_persistence_test_file.json").is_none());
+}
+
+#[test]
+fn load_snapshot_returns_none_for_invalid_json() {
+    let path = "/tmp/persistence_test_invalid.json";
+    fs::write(path, "not valid json").unwrap();
+
+    assert!(persistence::load_snapshot(path).is_none());
+
+    fs::remove_file(path).ok();
+}
+
+#[tokio::test]
+async fn persist_now_writes_a_snapshot_load_snapshot_can_read_back() {
+    let path = "/tmp/persistence_test_round_trip.json";
+    fs::remove_file(path).ok();
+
+    let config = create_test_config(path);
+    let vehicle = VehicleSimulator::new(config);
+    let fleet = Arc::new(Mutex::new(FleetSimulator::new(vec![vehicle])));
+
+    persistence::persist_now(path, &fleet, 0).await;
+
+    let loaded = persistence::load_snapshot(path).expect("snapshot should have been written");
+    let expected = fleet.lock().await.vehicles()[0].persisted_state();
+    assert_eq!(loaded.order_id, expected.order_id);
+    assert_eq!(loaded.last_node_id, expected.last_node_id);
+
+    fs::remove_file(path).ok();
+}
+
+#[test]
+fn persisted_state_round_trips_through_json() {
+    let snapshot = sample_persisted_state();
+    let serialized = serde_json::to_string(&snapshot).unwrap();
+    let deserialized: PersistedState = serde_json::from_str(&serialized).unwrap();
+
+    assert_eq!(deserialized.order_id, snapshot.order_id);
+    assert_eq!(deserialized.order_update_id, snapshot.order_update_id);
+    assert_eq!(deserialized.last_node_id, snapshot.last_node_id);
+    assert_eq!(deserialized.last_node_sequence_id, snapshot.last_node_sequence_id);
+}
+
+#[test]
+fn persisted_state_uses_camel_case_field_names() {
+    let json = serde_json::to_value(sample_persisted_state()).unwrap();
+
+    assert!(json.get("orderId").is_some());
+    assert!(json.get("orderUpdateId").is_some());
+    assert!(json.get("lastNodeId").is_some());
+    assert!(json.get("agvPosition").is_some());
+}
@@ -0,0 +1,75 @@
+use vda5050_vehicle_simulator::protocol::version::{decode_order, DecodeError};
+use vda5050_vehicle_simulator::protocol::vda_2_1_0::vda5050_2_1_0_order::{Node, Order};
+
+fn order_payload(version: &str, merge_node_id: Option<&str>) -> String {
+    let order = Order {
+        header_id: 1,
+        timestamp: "2024-01-01T00:00:00.00Z".to_string(),
+        version: version.to_string(),
+        manufacturer: "acme".to_string(),
+        serial_number: "agv-1".to_string(),
+        order_id: "order-1".to_string(),
+        order_update_id: 0,
+        zone_set_id: None,
+        nodes: vec![Node {
+            node_id: "n1".to_string(),
+            sequence_id: 0,
+            node_description: None,
+            released: true,
+            node_position: None,
+            actions: vec![],
+            merge_node_id: merge_node_id.map(String::from),
+        }],
+        edges: vec![],
+    };
+    serde_json::to_string(&order).unwrap()
+}
+
+#[test]
+fn accepts_same_major_same_minor() {
+    let payload = order_payload("2.0.0", None);
+    let order = decode_order(&payload, "2.0.0").expect("2.0.0 message accepted by 2.0.0 simulator");
+    assert_eq!(order.order_id, "order-1");
+}
+
+#[test]
+fn accepts_higher_minor_and_downcasts_unknown_fields() {
+    let payload = order_payload("2.1.0", Some("n2"));
+    let order = decode_order(&payload, "2.0.0")
+        .expect("2.1.0 message accepted by 2.0.0 simulator via downcast");
+    assert_eq!(order.nodes.len(), 1);
+    assert_eq!(order.nodes[0].node_id, "n1");
+}
+
+#[test]
+fn accepts_lower_minor_when_configured_for_higher() {
+    let payload = order_payload("2.0.0", None);
+    let order = decode_order(&payload, "2.1.0")
+        .expect("2.0.0 message accepted by 2.1.0-configured simulator");
+    assert_eq!(order.order_id, "order-1");
+}
+
+#[test]
+fn rejects_mismatched_major_version() {
+    let payload = order_payload("3.0.0", None);
+    let err = decode_order(&payload, "2.0.0").expect_err("major version mismatch must be rejected");
+    match err {
+        DecodeError::Version(e) => {
+            assert_eq!(e.configured_major, 2);
+            assert_eq!(e.message_version, "3.0.0");
+        }
+        DecodeError::Json(e) => panic!("expected a version error, got a JSON error: {}", e),
+    }
+}
+
+#[test]
+fn round_trips_order_through_both_version_paths() {
+    let v2_0_0_payload = order_payload("2.0.0", None);
+    let v2_1_0_payload = order_payload("2.1.0", Some("n2"));
+
+    let from_2_0_0 = decode_order(&v2_0_0_payload, "2.0.0").unwrap();
+    let from_2_1_0 = decode_order(&v2_1_0_payload, "2.0.0").unwrap();
+
+    assert_eq!(from_2_0_0.order_id, from_2_1_0.order_id);
+    assert_eq!(from_2_0_0.nodes[0].node_id, from_2_1_0.nodes[0].node_id);
+}
@@ -0,0 +1,213 @@
+use std::sync::{Arc, Mutex};
+
+use vda5050_vehicle_simulator::{
+    config::{AdminConfig, Config, OutboundQueueConfig, PersistenceConfig, MqttBrokerConfig, Settings, VehicleConfig},
+    protocol::vda_2_0_0::{
+        vda5050_2_0_0_action::{Action, ActionParameter, ActionParameterValue, BlockingType},
+        vda5050_2_0_0_instant_actions::InstantActions,
+        vda5050_2_0_0_order::Order,
+        vda5050_2_0_0_state::ActionStatus,
+    },
+    tracer::{InMemoryTracer, JsonLinesTracer, TraceEvent, Tracer},
+    utils,
+    vehicle_simulator::VehicleSimulator,
+};
+
+fn create_test_config() -> Config {
+    Config {
+        mqtt_broker: MqttBrokerConfig {
+            host: "localhost".to_string(),
+            port: "1883".to_string(),
+            vda_interface: "uagv".to_string(),
+            protocol_version: "3.1.1".to_string(),
+            reconnect_initial_backoff_ms: 500,
+            reconnect_max_backoff_secs: 30,
+            reconnect_max_attempts: 5,
+        },
+        vehicle: VehicleConfig {
+            serial_number: "TEST-AGV-001".to_string(),
+            manufacturer: "TEST".to_string(),
+            vda_version: "v2".to_string(),
+            vda_full_version: "2.0.0".to_string(),
+        },
+        settings: Settings {
+            map_id: "test_map".to_string(),
+            state_frequency: 1,
+            visualization_frequency: 5,
+            action_time: 0.0,
+            robot_count: 1,
+            speed: 0.1,
+            max_acceleration: 0.1,
+            max_deceleration: 0.1,
+        },
+        admin: AdminConfig {
+            enabled: false,
+            bind_address: "127.0.0.1:0".to_string(),
+        },
+        persistence: PersistenceConfig {
+            enabled: false,
+            path: "/tmp/test_vehicle_state.json".to_string(),
+            save_interval_secs: 30,
+        },
+        outbound_queue: OutboundQueueConfig {
+            visualization_buffer_depth: 10,
+            drop_policy: "drop_oldest".to_string(),
+        },
+    }
+}
+
+fn create_init_position_action() -> Action {
+    Action {
+        action_type: "initPosition".to_string(),
+        action_id: "init_pos_001".to_string(),
+        action_description: Some("Initialize vehicle position".to_string()),
+        blocking_type: BlockingType::Hard,
+        action_parameters: Some(vec![
+            ActionParameter {
+                key: "x".to_string(),
+                value: ActionParameterValue::Float(0.0),
+            },
+            ActionParameter {
+                key: "y".to_string(),
+                value: ActionParameterValue::Float(0.0),
+            },
+        ]),
+    }
+}
+
+fn create_small_order() -> Order {
+    Order {
+        header_id: 1,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        order_id: "order_001".to_string(),
+        order_update_id: 0,
+        zone_set_id: None,
+        nodes: vec![],
+        edges: vec![],
+    }
+}
+
+#[test]
+fn action_transitions_trace_in_lifecycle_order() {
+    let mut simulator = VehicleSimulator::new(create_test_config());
+    let tracer = Arc::new(Mutex::new(InMemoryTracer::new()));
+    simulator.set_tracer(Box::new(Arc::clone(&tracer)));
+
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 1,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![create_init_position_action()],
+    });
+
+    // Waiting -> Initializing -> Running -> Finished: one lifecycle step per call.
+    simulator.process_instant_actions();
+    simulator.process_instant_actions();
+    simulator.process_instant_actions();
+
+    let events: Vec<TraceEvent> = tracer.lock().unwrap().events().cloned().collect();
+
+    assert_eq!(
+        events,
+        vec![
+            TraceEvent::ActionTransition {
+                action_id: "init_pos_001".to_string(),
+                from: ActionStatus::Waiting,
+                to: ActionStatus::Initializing,
+            },
+            TraceEvent::ActionTransition {
+                action_id: "init_pos_001".to_string(),
+                from: ActionStatus::Initializing,
+                to: ActionStatus::Running,
+            },
+            TraceEvent::ActionTransition {
+                action_id: "init_pos_001".to_string(),
+                from: ActionStatus::Running,
+                to: ActionStatus::Finished,
+            },
+        ]
+    );
+}
+
+#[test]
+fn order_rejection_trace_includes_reason() {
+    let mut simulator = VehicleSimulator::new(create_test_config());
+    let tracer = Arc::new(Mutex::new(InMemoryTracer::new()));
+    simulator.set_tracer(Box::new(Arc::clone(&tracer)));
+
+    // The vehicle's position has never been initialized, so it isn't ready for a new order yet.
+    simulator.process_order(create_small_order());
+
+    let events: Vec<TraceEvent> = tracer.lock().unwrap().events().cloned().collect();
+
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        TraceEvent::OrderRejected { reason } => {
+            assert!(!reason.is_empty());
+        }
+        other => panic!("expected an OrderRejected event, got {:?}", other),
+    }
+}
+
+#[test]
+fn ticks_are_monotonic_and_independent_of_wall_clock() {
+    let mut simulator = VehicleSimulator::new(create_test_config());
+    let tracer = Arc::new(Mutex::new(InMemoryTracer::new()));
+    simulator.set_tracer(Box::new(Arc::clone(&tracer)));
+
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 1,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![create_init_position_action()],
+    });
+
+    simulator.update_state();
+    simulator.update_state();
+    simulator.update_state();
+
+    let ticks: Vec<u64> = tracer.lock().unwrap().events.iter().map(|(tick, _)| *tick).collect();
+
+    assert_eq!(ticks, vec![1, 2, 3]);
+}
+
+#[test]
+fn json_lines_tracer_writes_one_object_per_line() {
+    let mut buffer = Vec::new();
+    {
+        let mut tracer = JsonLinesTracer::new(&mut buffer);
+        tracer.trace(
+            0,
+            TraceEvent::OrderAccepted {
+                order_id: "order_001".to_string(),
+            },
+        );
+        tracer.trace(
+            1,
+            TraceEvent::NodeArrived {
+                node_id: "node_001".to_string(),
+            },
+        );
+    }
+
+    let output = String::from_utf8(buffer).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+
+    let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(first["tick"], 0);
+    assert_eq!(first["event"], "orderAccepted");
+    assert_eq!(first["orderId"], "order_001");
+
+    let second: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+    assert_eq!(second["tick"], 1);
+    assert_eq!(second["event"], "nodeArrived");
+    assert_eq!(second["nodeId"], "node_001");
+}
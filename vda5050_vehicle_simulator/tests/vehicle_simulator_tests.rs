@@ -1,22 +1,44 @@
+use paho_mqtt as mqtt;
 use vda5050_vehicle_simulator::{
     vehicle_simulator::VehicleSimulator,
-    config::{Config, MqttBrokerConfig, VehicleConfig, Settings},
+    config::{AdminConfig, Config, OutboundQueueConfig, PersistenceConfig, MqttBrokerConfig, VehicleConfig, Settings},
     protocol::vda_2_0_0::{
         vda5050_2_0_0_action::{Action, ActionParameter, ActionParameterValue, BlockingType},
         vda5050_2_0_0_instant_actions::InstantActions,
         vda5050_2_0_0_order::{Order, Node, Edge},
         vda5050_2_0_0_state::ActionStatus,
     },
-    protocol::vda5050_common::NodePosition,
+    protocol::vda5050_common::{ControlPoint, NodePosition, Trajectory},
     utils,
 };
 
+/// Exact NURBS representation of a quarter circle of the given `radius`, centered on the
+/// origin, running counterclockwise from `(radius, 0)` to `(0, radius)`. The middle control
+/// point's weight of `sqrt(2)/2` is what makes a degree-2 rational B-spline trace a true circular
+/// arc rather than a parabola.
+fn quarter_circle_trajectory(radius: f32) -> Trajectory {
+    let corner_weight = std::f32::consts::FRAC_1_SQRT_2;
+    Trajectory {
+        degree: 2,
+        knot_vector: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+        control_points: vec![
+            ControlPoint { x: radius, y: 0.0, weight: Some(1.0), orientation: None },
+            ControlPoint { x: radius, y: radius, weight: Some(corner_weight), orientation: None },
+            ControlPoint { x: 0.0, y: radius, weight: Some(1.0), orientation: None },
+        ],
+    }
+}
+
 fn create_test_config() -> Config {
     Config {
         mqtt_broker: MqttBrokerConfig {
             host: "localhost".to_string(),
             port: "1883".to_string(),
             vda_interface: "uagv".to_string(),
+            protocol_version: "3.1.1".to_string(),
+            reconnect_initial_backoff_ms: 500,
+            reconnect_max_backoff_secs: 30,
+            reconnect_max_attempts: 5,
         },
         vehicle: VehicleConfig {
             serial_number: "TEST-AGV-001".to_string(),
@@ -28,13 +50,44 @@ fn create_test_config() -> Config {
             map_id: "test_map".to_string(),
             state_frequency: 1,
             visualization_frequency: 5,
-            action_time: 1.0,
+            action_time: 0.0,
             robot_count: 1,
             speed: 0.1,
+            max_acceleration: 0.1,
+            max_deceleration: 0.1,
+        },
+        admin: AdminConfig {
+            enabled: false,
+            bind_address: "127.0.0.1:0".to_string(),
+        },
+        persistence: PersistenceConfig {
+            enabled: false,
+            path: "/tmp/test_vehicle_state.json".to_string(),
+            save_interval_secs: 30,
+        },
+        outbound_queue: OutboundQueueConfig {
+            visualization_buffer_depth: 10,
+            drop_policy: "drop_oldest".to_string(),
         },
     }
 }
 
+/// Drive every known instant action through the full `Waiting -> Initializing -> Running ->
+/// Finished` lifecycle by repeatedly calling `process_instant_actions`, one step per call.
+fn drain_instant_actions(simulator: &mut VehicleSimulator) {
+    for _ in 0..10 {
+        simulator.process_instant_actions();
+        if simulator
+            .state
+            .action_states
+            .iter()
+            .all(|action_state| matches!(action_state.action_status, ActionStatus::Finished | ActionStatus::Failed))
+        {
+            return;
+        }
+    }
+}
+
 fn create_init_position_action() -> Action {
     Action {
         action_type: "initPosition".to_string(),
@@ -157,10 +210,14 @@ fn test_init_position_instant_action() {
     assert_eq!(simulator.state.action_states[0].action_id, "init_pos_001");
     assert_eq!(simulator.state.action_states[0].action_status, ActionStatus::Waiting);
     
-    // Process instant actions
+    // Process instant actions: one lifecycle step per call.
+    simulator.process_instant_actions();
+    assert_eq!(simulator.state.action_states[0].action_status, ActionStatus::Initializing);
+
+    simulator.process_instant_actions();
+    assert_eq!(simulator.state.action_states[0].action_status, ActionStatus::Running);
+
     simulator.process_instant_actions();
-    
-    // Verify action was executed and finished
     assert_eq!(simulator.state.action_states[0].action_status, ActionStatus::Finished);
     
     // Verify position was updated
@@ -196,11 +253,11 @@ fn test_small_order_completion() {
     };
     
     simulator.accept_instant_actions(instant_actions);
-    simulator.process_instant_actions();
-    
+    drain_instant_actions(&mut simulator);
+
     // Verify position is initialized
     assert!(simulator.state.agv_position.as_ref().unwrap().position_initialized);
-    
+
     // Create and process small order
     let order = create_small_order();
     simulator.process_order(order);
@@ -322,8 +379,8 @@ fn test_vehicle_ready_for_new_order() {
     };
     
     simulator.accept_instant_actions(instant_actions);
-    simulator.process_instant_actions();
-    
+    drain_instant_actions(&mut simulator);
+
     // Now vehicle should be ready
     assert!(simulator.is_vehicle_ready_for_new_order());
 }
@@ -367,9 +424,485 @@ fn test_action_state_management() {
     assert_eq!(action_state.action_type, Some("initPosition".to_string()));
     
     // Process the action
-    simulator.process_instant_actions();
-    
+    drain_instant_actions(&mut simulator);
+
     // Verify action state was updated
     let action_state = &simulator.state.action_states[0];
     assert_eq!(action_state.action_status, ActionStatus::Finished);
+}
+
+#[test]
+fn test_trajectory_following_stays_on_quarter_circle() {
+    let mut config = create_test_config();
+    config.settings.speed = 0.2;
+    let mut simulator = VehicleSimulator::new(config);
+
+    let radius = 5.0;
+    let allowed_deviation_xy = 0.1;
+
+    // Initialize the vehicle exactly on the start of the arc, as `initPosition` would.
+    let init_action = Action {
+        action_type: "initPosition".to_string(),
+        action_id: "init_pos_001".to_string(),
+        action_description: None,
+        blocking_type: BlockingType::Hard,
+        action_parameters: Some(vec![
+            ActionParameter { key: "x".to_string(), value: ActionParameterValue::Float(radius) },
+            ActionParameter { key: "y".to_string(), value: ActionParameterValue::Float(0.0) },
+            ActionParameter { key: "theta".to_string(), value: ActionParameterValue::Float(0.0) },
+            ActionParameter { key: "mapId".to_string(), value: ActionParameterValue::Str("test_map".to_string()) },
+            ActionParameter { key: "lastNodeId".to_string(), value: ActionParameterValue::Str("arc_start".to_string()) },
+        ]),
+    };
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 1,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![init_action],
+    });
+    drain_instant_actions(&mut simulator);
+
+    let order = Order {
+        header_id: 1,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        order_id: "order_arc".to_string(),
+        order_update_id: 0,
+        zone_set_id: None,
+        nodes: vec![
+            Node {
+                node_id: "arc_start".to_string(),
+                sequence_id: 0,
+                node_description: None,
+                released: true,
+                node_position: Some(NodePosition {
+                    x: radius,
+                    y: 0.0,
+                    theta: None,
+                    allowed_deviation_xy: Some(allowed_deviation_xy),
+                    allowed_deviation_theta: None,
+                    map_id: "test_map".to_string(),
+                    map_description: None,
+                }),
+                actions: vec![],
+            },
+            Node {
+                node_id: "arc_end".to_string(),
+                sequence_id: 1,
+                node_description: None,
+                released: true,
+                node_position: Some(NodePosition {
+                    x: 0.0,
+                    y: radius,
+                    theta: None,
+                    allowed_deviation_xy: Some(allowed_deviation_xy),
+                    allowed_deviation_theta: None,
+                    map_id: "test_map".to_string(),
+                    map_description: None,
+                }),
+                actions: vec![],
+            },
+        ],
+        edges: vec![
+            Edge {
+                edge_id: "arc_edge".to_string(),
+                sequence_id: 0,
+                edge_description: None,
+                released: true,
+                start_node_id: "arc_start".to_string(),
+                end_node_id: "arc_end".to_string(),
+                max_speed: None,
+                max_height: None,
+                min_height: None,
+                orientation: None,
+                orientation_type: None,
+                direction: None,
+                rotation_allowed: None,
+                max_rotation_speed: None,
+                length: None,
+                trajectory: Some(quarter_circle_trajectory(radius)),
+                actions: vec![],
+            },
+        ],
+    };
+    simulator.process_order(order);
+
+    for _ in 0..200 {
+        simulator.update_state();
+
+        let position = simulator.state.agv_position.as_ref().unwrap();
+        let distance_from_center = utils::get_distance(position.x, position.y, 0.0, 0.0);
+        assert!(
+            (distance_from_center - radius).abs() <= allowed_deviation_xy,
+            "position ({}, {}) drifted off the quarter circle: distance_from_center = {}",
+            position.x,
+            position.y,
+            distance_from_center,
+        );
+
+        if simulator.state.node_states.is_empty() && simulator.state.edge_states.is_empty() {
+            break;
+        }
+    }
+
+    assert!(simulator.state.node_states.is_empty());
+    assert!(simulator.state.edge_states.is_empty());
+
+    let final_position = simulator.state.agv_position.as_ref().unwrap();
+    let distance_to_target = utils::get_distance(final_position.x, final_position.y, 0.0, radius);
+    assert!(distance_to_target < 0.5, "final position too far from arc end: distance = {}", distance_to_target);
+}
+
+fn control_action(action_id: &str, action_type: &str, blocking_type: BlockingType) -> Action {
+    Action {
+        action_type: action_type.to_string(),
+        action_id: action_id.to_string(),
+        action_description: None,
+        blocking_type,
+        action_parameters: None,
+    }
+}
+
+fn action_status(simulator: &VehicleSimulator, action_id: &str) -> ActionStatus {
+    simulator
+        .state
+        .action_states
+        .iter()
+        .find(|action_state| action_state.action_id == action_id)
+        .unwrap()
+        .action_status
+}
+
+fn simulator_with_initialized_position() -> VehicleSimulator {
+    let mut simulator = VehicleSimulator::new(create_test_config());
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 1,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![create_init_position_action()],
+    });
+    drain_instant_actions(&mut simulator);
+    simulator
+}
+
+/// Base order `order_002` with a released base (`node_001` -> `node_002`) followed by an
+/// unreleased horizon node (`node_003`), matching `order_update_id`.
+fn create_order_with_horizon(order_update_id: u32) -> Order {
+    let mut order = create_small_order();
+    order.order_id = "order_002".to_string();
+    order.order_update_id = order_update_id;
+    order.nodes.push(Node {
+        node_id: "node_003".to_string(),
+        sequence_id: 3,
+        node_description: Some("Horizon node".to_string()),
+        released: false,
+        node_position: Some(NodePosition {
+            x: 20.0,
+            y: 30.0,
+            theta: Some(0.0),
+            allowed_deviation_xy: Some(0.1),
+            allowed_deviation_theta: Some(0.1),
+            map_id: "test_map".to_string(),
+            map_description: None,
+        }),
+        actions: vec![],
+    });
+    order.edges.push(Edge {
+        edge_id: "edge_002".to_string(),
+        sequence_id: 2,
+        edge_description: Some("Path from node_002 to the horizon".to_string()),
+        released: false,
+        start_node_id: "node_002".to_string(),
+        end_node_id: "node_003".to_string(),
+        max_speed: Some(0.5),
+        max_height: None,
+        min_height: None,
+        orientation: None,
+        orientation_type: None,
+        direction: None,
+        rotation_allowed: Some(true),
+        max_rotation_speed: None,
+        length: Some(6.5),
+        trajectory: None,
+        actions: vec![],
+    });
+    order
+}
+
+#[test]
+fn test_initial_order_with_horizon_is_accepted_as_base_plus_preview() {
+    let mut simulator = simulator_with_initialized_position();
+
+    simulator.process_order(create_order_with_horizon(0));
+
+    assert_eq!(simulator.state.order_id, "order_002");
+    assert_eq!(simulator.state.order_update_id, 0);
+    assert_eq!(simulator.state.node_states.len(), 3);
+    assert_eq!(simulator.state.node_states[2].node_id, "node_003");
+    assert_eq!(simulator.state.node_states[2].released, false);
+    assert_eq!(simulator.state.edge_states.len(), 2);
+    assert_eq!(simulator.state.edge_states[1].released, false);
+}
+
+#[test]
+fn test_order_update_releases_horizon_without_resetting_progress() {
+    let mut simulator = simulator_with_initialized_position();
+    simulator.process_order(create_order_with_horizon(0));
+
+    // Drive the vehicle onto the released base so it has made real, non-zero progress: it
+    // should stop right at node_002, since node_003 beyond it is still unreleased horizon.
+    for _ in 0..200 {
+        simulator.update_state();
+        if simulator.state.last_node_sequence_id == 2 {
+            break;
+        }
+    }
+    assert_eq!(simulator.state.last_node_sequence_id, 2);
+    assert_eq!(simulator.state.node_states.len(), 2, "should be stuck at the horizon boundary");
+
+    // Snap the AGV exactly onto node_002 so the update's proximity check is deterministic.
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 2,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![Action {
+            action_type: "initPosition".to_string(),
+            action_id: "snap_to_node_002".to_string(),
+            action_description: None,
+            blocking_type: BlockingType::Hard,
+            action_parameters: Some(vec![
+                ActionParameter { key: "x".to_string(), value: ActionParameterValue::Float(15.0) },
+                ActionParameter { key: "y".to_string(), value: ActionParameterValue::Float(25.0) },
+                ActionParameter { key: "theta".to_string(), value: ActionParameterValue::Float(0.0) },
+                ActionParameter { key: "mapId".to_string(), value: ActionParameterValue::Str("test_map".to_string()) },
+                ActionParameter { key: "lastNodeId".to_string(), value: ActionParameterValue::Str("node_002".to_string()) },
+            ]),
+        }],
+    });
+    drain_instant_actions(&mut simulator);
+
+    let mut update = create_order_with_horizon(1);
+    update.nodes[2].released = true;
+    update.edges[1].released = true;
+
+    simulator.process_order(update);
+
+    assert_eq!(simulator.state.order_update_id, 1);
+    // Progress already made must survive the update unchanged.
+    assert_eq!(simulator.state.last_node_sequence_id, 2);
+    let node_3 = simulator.state.node_states.iter().find(|node| node.node_id == "node_003").unwrap();
+    assert_eq!(node_3.released, true);
+    let edge_2 = simulator.state.edge_states.iter().find(|edge| edge.edge_id == "edge_002").unwrap();
+    assert_eq!(edge_2.released, true);
+}
+
+#[test]
+fn test_order_update_rejected_when_order_update_id_is_not_contiguous() {
+    let mut simulator = simulator_with_initialized_position();
+    simulator.process_order(create_order_with_horizon(0));
+
+    let mut update = create_order_with_horizon(2); // skips update_id 1
+    update.nodes[2].released = true;
+    update.edges[1].released = true;
+
+    simulator.process_order(update);
+
+    // The stale/discontinuous update must be rejected outright: nothing about the tracked
+    // order changes.
+    assert_eq!(simulator.state.order_update_id, 0);
+    let node_3 = simulator.state.node_states.iter().find(|node| node.node_id == "node_003").unwrap();
+    assert_eq!(node_3.released, false);
+}
+
+#[test]
+fn test_hard_blocking_action_forbids_concurrent_actions() {
+    let mut simulator = simulator_with_initialized_position();
+
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 2,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![
+            control_action("hard_1", "noop", BlockingType::Hard),
+            control_action("hard_2", "noop", BlockingType::None),
+        ],
+    });
+
+    // One step: hard_1 starts initializing, hard_2 is still Waiting (it was registered after).
+    simulator.process_instant_actions();
+    assert_eq!(action_status(&simulator, "hard_1"), ActionStatus::Initializing);
+    assert_eq!(action_status(&simulator, "hard_2"), ActionStatus::Waiting);
+
+    // hard_1 becomes Running; hard_2 must stay Waiting even though its own blocking_type is None,
+    // because a Hard action running anywhere forbids every other action from starting.
+    simulator.process_instant_actions();
+    assert_eq!(action_status(&simulator, "hard_1"), ActionStatus::Running);
+    assert_eq!(action_status(&simulator, "hard_2"), ActionStatus::Waiting);
+
+    // hard_1 finishes (action_time is 0.0); only then can hard_2 start.
+    simulator.process_instant_actions();
+    assert_eq!(action_status(&simulator, "hard_1"), ActionStatus::Finished);
+    assert_eq!(action_status(&simulator, "hard_2"), ActionStatus::Waiting);
+
+    simulator.process_instant_actions();
+    assert_eq!(action_status(&simulator, "hard_2"), ActionStatus::Initializing);
+}
+
+#[test]
+fn test_none_blocking_actions_run_concurrently() {
+    let mut simulator = simulator_with_initialized_position();
+
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 2,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![
+            control_action("none_1", "noop", BlockingType::None),
+            control_action("none_2", "noop", BlockingType::None),
+        ],
+    });
+
+    // Both actions start on the same step since neither blocks the other.
+    simulator.process_instant_actions();
+    assert_eq!(action_status(&simulator, "none_1"), ActionStatus::Initializing);
+    assert_eq!(action_status(&simulator, "none_2"), ActionStatus::Initializing);
+
+    simulator.process_instant_actions();
+    assert_eq!(action_status(&simulator, "none_1"), ActionStatus::Running);
+    assert_eq!(action_status(&simulator, "none_2"), ActionStatus::Running);
+}
+
+#[test]
+fn test_hard_blocking_action_halts_motion() {
+    let mut simulator = simulator_with_initialized_position();
+
+    simulator.process_order(create_small_order());
+    assert_eq!(simulator.state.node_states.len(), 2);
+
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 2,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![control_action("hard_1", "noop", BlockingType::Hard)],
+    });
+
+    simulator.update_state(); // hard_1: Waiting -> Initializing (motion may still proceed this tick)
+
+    let position_before = simulator.state.agv_position.as_ref().unwrap().clone();
+    let node_states_before = simulator.state.node_states.len();
+
+    // hard_1 becomes Running during this very call, so motion must be blocked in the same tick.
+    simulator.update_state();
+    assert_eq!(action_status(&simulator, "hard_1"), ActionStatus::Running);
+    let position_after = simulator.state.agv_position.as_ref().unwrap();
+    assert_eq!(position_after.x, position_before.x);
+    assert_eq!(position_after.y, position_before.y);
+    assert_eq!(simulator.state.node_states.len(), node_states_before, "order progress must not advance while hard-blocked");
+}
+
+#[test]
+fn test_pause_and_resume_instant_actions() {
+    let mut simulator = simulator_with_initialized_position();
+
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 2,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![control_action("long_running", "noop", BlockingType::Soft)],
+    });
+    simulator.process_instant_actions(); // Waiting -> Initializing
+    simulator.process_instant_actions(); // Initializing -> Running
+    assert_eq!(action_status(&simulator, "long_running"), ActionStatus::Running);
+
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 3,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![control_action("pause_1", "pause", BlockingType::None)],
+    });
+    drain_instant_actions(&mut simulator);
+    assert_eq!(simulator.state.paused, Some(true));
+    assert_eq!(action_status(&simulator, "long_running"), ActionStatus::Paused);
+
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 4,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![control_action("resume_1", "resume", BlockingType::None)],
+    });
+    drain_instant_actions(&mut simulator);
+    assert_eq!(simulator.state.paused, Some(false));
+    assert_eq!(action_status(&simulator, "long_running"), ActionStatus::Running);
+}
+
+#[test]
+fn test_cancel_order_instant_action_fails_in_progress_actions_and_clears_states() {
+    let mut simulator = simulator_with_initialized_position();
+
+    simulator.process_order(create_small_order());
+    assert_eq!(simulator.state.node_states.len(), 2);
+
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 2,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![control_action("running_action", "noop", BlockingType::None)],
+    });
+    simulator.process_instant_actions();
+    simulator.process_instant_actions();
+    assert_eq!(action_status(&simulator, "running_action"), ActionStatus::Running);
+
+    simulator.accept_instant_actions(InstantActions {
+        header_id: 3,
+        timestamp: utils::get_timestamp(),
+        version: "2.0.0".to_string(),
+        manufacturer: "TEST".to_string(),
+        serial_number: "TEST-AGV-001".to_string(),
+        actions: vec![control_action("cancel_1", "cancelOrder", BlockingType::None)],
+    });
+    drain_instant_actions(&mut simulator);
+
+    assert_eq!(action_status(&simulator, "running_action"), ActionStatus::Failed);
+    assert!(simulator.state.node_states.is_empty());
+    assert!(simulator.state.edge_states.is_empty());
+}
+
+/// `last_will()` is registered as the MQTT Last-Will-and-Testament on connect, so it must be
+/// retained at QoS 1 on the connection topic: that's what lets a fleet manager that was never
+/// subscribed during the crash still learn about it from a late subscribe.
+#[test]
+fn test_last_will_is_retained_qos_1_connection_broken_message() {
+    let simulator = VehicleSimulator::new(create_test_config());
+
+    let last_will = simulator.last_will();
+
+    assert!(last_will.topic().ends_with("/connection"));
+    assert_eq!(last_will.qos(), mqtt::QOS_1);
+    assert!(last_will.retained());
+
+    let payload: serde_json::Value = serde_json::from_slice(last_will.payload()).unwrap();
+    assert_eq!(payload["connectionState"], "CONNECTIONBROKEN");
+}
 } 
\ No newline at end of file